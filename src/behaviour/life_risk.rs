@@ -0,0 +1,87 @@
+use crate::model::*;
+use std::collections::HashMap;
+
+/// Shifts the aggression of a unit by what its death would cost: a unit with extra lives
+/// comes back, the last life is kept at all costs, a freshly landed unit still has the spawn shield
+#[derive(Clone, Debug)]
+pub struct LifeRisk {
+    /// Aggression shift of a unit which respawns if killed and has all extra lives left, it is
+    /// graded down to the last life shift as the lives are spent
+    pub spare_life_shift: f64,
+    /// Aggression shift of a unit on its last life, usually negative
+    pub last_life_shift: f64,
+    /// Aggression shift while the spawn shield is still up
+    pub spawn_shield_shift: f64,
+    /// Seconds after landing the spawn shield is relied on
+    pub spawn_shield_window: f64,
+    /// Respawning which stops sooner than that is not counted on (seconds)
+    pub respawn_margin: f64,
+    landed_ticks: HashMap<i32, i32>,
+}
+
+impl Default for LifeRisk {
+    fn default() -> Self {
+        Self {
+            spare_life_shift: 0.15,
+            last_life_shift: -0.25,
+            spawn_shield_shift: 0.2,
+            spawn_shield_window: 3.0,
+            respawn_margin: 5.0,
+            landed_ticks: HashMap::new(),
+        }
+    }
+}
+
+impl LifeRisk {
+    /// Remembers when our units land after spawning, forgets units which are gone for good
+    pub fn update(&mut self, game: &Game) {
+        self.landed_ticks
+            .retain(|id, _| game.units.iter().any(|u| u.id == *id));
+        for unit in game.units.iter().filter(|u| u.player_id == game.my_id) {
+            if unit.remaining_spawn_time.is_some() {
+                self.landed_ticks.remove(&unit.id);
+            } else {
+                self.landed_ticks
+                    .entry(unit.id)
+                    .or_insert(game.current_tick);
+            }
+        }
+    }
+
+    /// Whether the unit would come back if killed during a fight starting now
+    pub fn will_respawn(&self, me: &Unit, zone: &Zone, constants: &Constants) -> bool {
+        let radius = zone.radius_after(self.respawn_margin, constants);
+        me.can_respawn(zone, constants) && radius > constants.last_respawn_zone_radius
+    }
+
+    /// Whether the unit has just landed and still has most of the spawn shield
+    pub fn is_shielded(&self, me: &Unit, current_tick: i32, constants: &Constants) -> bool {
+        let window = (self.spawn_shield_window * constants.ticks_per_second) as i32;
+        self.landed_ticks
+            .get(&me.id)
+            .is_some_and(|&tick| current_tick - tick <= window)
+            && me.shield >= constants.spawn_shield * 0.5
+    }
+
+    pub fn aggression_shift(
+        &self,
+        me: &Unit,
+        zone: &Zone,
+        current_tick: i32,
+        constants: &Constants,
+    ) -> f64 {
+        let lives = if self.will_respawn(me, zone, constants) {
+            let spare = me.extra_lives.min(constants.extra_lives) as f64
+                / constants.extra_lives.max(1) as f64;
+            self.last_life_shift + (self.spare_life_shift - self.last_life_shift) * spare
+        } else {
+            self.last_life_shift
+        };
+        let shield = if self.is_shielded(me, current_tick, constants) {
+            self.spawn_shield_shift
+        } else {
+            0.0
+        };
+        lives + shield
+    }
+}
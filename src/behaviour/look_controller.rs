@@ -0,0 +1,174 @@
+use crate::model::*;
+use crate::potential_field::{normalize_angle, Behaviour, BehaviourGroup, PotentialField};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+/// Ticks after which a sound is not worth looking at
+const SOUND_MEMORY_TICKS: f64 = 50.0;
+
+/// Chooses where to look when there is nobody to aim at. Directions are scored by what
+/// would get into the field of view, minus the time to turn there. The unit sweeps: a chosen
+/// direction is kept until the unit has turned there, unless another one gets much better.
+#[derive(Clone, Debug)]
+pub struct LookController {
+    /// Number of sectors around the unit
+    pub sectors: usize,
+    /// Ticks after which a sector is considered completely unknown
+    pub stale_ticks: f64,
+    pub sound_weight: f64,
+    pub enemy_weight: f64,
+    pub stale_weight: f64,
+    /// Enemies are likely to come from the zone center
+    pub approach_weight: f64,
+    /// Looking where we walk
+    pub movement_weight: f64,
+    /// Penalty for every second of turning
+    pub turn_weight: f64,
+    /// How much better another direction must be to abandon the current sweep
+    pub switch_margin: f64,
+    /// Units running for their lives look only that far from the movement direction, degrees
+    pub survive_arc: f64,
+    last_seen: HashMap<i32, Vec<i32>>,
+    sweeps: HashMap<i32, f64>,
+}
+
+impl Default for LookController {
+    fn default() -> Self {
+        Self {
+            sectors: 16,
+            stale_ticks: 120.0,
+            sound_weight: 2.0,
+            enemy_weight: 2.0,
+            stale_weight: 1.0,
+            approach_weight: 0.5,
+            movement_weight: 0.5,
+            turn_weight: 1.0,
+            switch_margin: 0.5,
+            survive_arc: 90.0,
+            last_seen: HashMap::new(),
+            sweeps: HashMap::new(),
+        }
+    }
+}
+
+fn sector_angle(sector: usize, sectors: usize) -> f64 {
+    normalize_angle(sector as f64 * 2.0 * PI / sectors as f64)
+}
+
+impl LookController {
+    fn is_in_cone(angle: f64, center: f64, fov: f64) -> bool {
+        normalize_angle(angle - center).abs() <= fov / 2.0
+    }
+
+    /// Directions worth looking at and their weights
+    fn interests(&self, pp: &PotentialField, me: &Unit, velocity: Vec2) -> Vec<(f64, f64)> {
+        let tick = pp.current_tick();
+        let angle_to = |position: Vec2| (position - me.position).angle();
+        let recency =
+            |sound_tick: i32| (1.0 - (tick - sound_tick) as f64 / SOUND_MEMORY_TICKS).max(0.0);
+
+        let mut interests = Vec::new();
+        interests.extend(
+            pp.shooting_sounds
+                .iter()
+                .map(|(sound, _, t)| (angle_to(sound.position), self.sound_weight * recency(*t))),
+        );
+        interests.extend(
+            pp.hit_sounds
+                .iter()
+                .chain(pp.steps_sounds.iter())
+                .map(|(sound, t)| (angle_to(sound.position), self.sound_weight * recency(*t))),
+        );
+        interests.extend(
+            pp.enemies
+                .remembered()
+                .map(|e| (angle_to(e.unit.position), self.enemy_weight * e.confidence)),
+        );
+        let center = pp.zone().current_center;
+        if center.distance_to(&me.position) > pp.constants().unit_radius {
+            interests.push((angle_to(center), self.approach_weight));
+        }
+        if velocity.length() > 0.0 {
+            interests.push((velocity.angle(), self.movement_weight));
+        }
+        interests
+    }
+
+    /// Direction to look at, the unit turns there with `rotation_speed`
+    pub fn direction(
+        &mut self,
+        pp: &PotentialField,
+        me: &Unit,
+        velocity: Vec2,
+        behaviour: Behaviour,
+    ) -> Vec2 {
+        let constants = pp.constants();
+        let tick = pp.current_tick();
+        let fov = me.fov_angle(constants);
+
+        let sectors = self.sectors;
+        let last_seen = self
+            .last_seen
+            .entry(me.id)
+            .or_insert_with(|| vec![i32::MIN / 2; sectors]);
+        let current = me.direction.angle();
+        for (sector, seen) in last_seen.iter_mut().enumerate() {
+            if Self::is_in_cone(sector_angle(sector, sectors), current, fov) {
+                *seen = tick;
+            }
+        }
+        let staleness = last_seen
+            .iter()
+            .map(|&seen| ((tick - seen) as f64 / self.stale_ticks).min(1.0))
+            .collect::<Vec<f64>>();
+
+        let arc = if behaviour.group() == BehaviourGroup::Survive && velocity.length() > 0.0 {
+            Some((velocity.angle(), self.survive_arc.to_radians()))
+        } else {
+            None
+        };
+        let is_allowed =
+            |angle: f64| arc.map_or(true, |(center, arc)| Self::is_in_cone(angle, center, arc));
+
+        let interests = self.interests(pp, me, velocity);
+        let score = |angle: f64| {
+            let stale = (0..sectors)
+                .filter(|&s| Self::is_in_cone(sector_angle(s, sectors), angle, fov))
+                .map(|s| staleness[s])
+                .sum::<f64>()
+                / sectors as f64;
+            let interest = interests
+                .iter()
+                .filter(|(a, _)| Self::is_in_cone(*a, angle, fov))
+                .map(|(_, w)| w)
+                .sum::<f64>();
+            let turn_time =
+                normalize_angle(angle - current).abs().to_degrees() / constants.rotation_speed;
+
+            interest + self.stale_weight * stale - self.turn_weight * turn_time
+        };
+        let best = (0..sectors)
+            .map(|sector| sector_angle(sector, sectors))
+            .filter(|&angle| is_allowed(angle))
+            .map(|angle| (angle, score(angle)))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        // keep sweeping to the chosen direction until the unit has turned there
+        let turn_per_tick = (constants.rotation_speed / constants.ticks_per_second).to_radians();
+        let sweep = self.sweeps.get(&me.id).cloned().filter(|&target| {
+            normalize_angle(target - current).abs() > turn_per_tick
+                && is_allowed(target)
+                && best.map_or(true, |(_, best_score)| {
+                    score(target) + self.switch_margin >= best_score
+                })
+        });
+        let target = match (sweep, best) {
+            (Some(target), _) => target,
+            (None, Some((angle, _))) => angle,
+            (None, None) => arc.map_or(current, |(center, _)| center),
+        };
+        self.sweeps.insert(me.id, target);
+
+        Vec2::new(target.cos(), target.sin())
+    }
+}
@@ -0,0 +1,9 @@
+mod life_risk;
+mod look_controller;
+mod posture;
+mod state_machine;
+
+pub use life_risk::*;
+pub use look_controller::*;
+pub use posture::*;
+pub use state_machine::*;
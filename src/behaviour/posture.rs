@@ -0,0 +1,167 @@
+use crate::behaviour::{BehaviourMachine, LifeRisk};
+use crate::model::*;
+use crate::potential_field::{PotentialField, TrackedEnemy};
+
+/// Chances of a fight and how much damage it brings
+#[derive(Copy, Clone, Debug)]
+pub struct FightOdds {
+    pub kill_probability: f64,
+    pub death_probability: f64,
+    /// Expected part of the enemy's health and shield we deal
+    pub damage_fraction: f64,
+}
+
+/// Estimates how much fighting is worth compared to surviving for the current standings
+#[derive(Clone, Debug)]
+pub struct StrategicPosture {
+    /// Probability to kill the enemy in a fight we take when there are no enemies around
+    pub kill_probability: f64,
+    /// Probability to lose a unit in a fight we take when there are no enemies around
+    pub death_probability: f64,
+    /// Part of the dealt damage which ends in kills before we have our own statistics
+    pub kill_rate: f64,
+    /// Part of the remaining teams we expect to outlive if we play safe
+    pub outlive_fraction: f64,
+    /// How much being behind the leader in score raises the aggression
+    pub standings_weight: f64,
+}
+
+impl Default for StrategicPosture {
+    fn default() -> Self {
+        Self {
+            kill_probability: 0.5,
+            death_probability: 0.3,
+            kill_rate: 0.5,
+            outlive_fraction: 0.5,
+            standings_weight: 0.3,
+        }
+    }
+}
+
+impl StrategicPosture {
+    /// Players who are still in the game share the smallest place
+    fn alive_players(game: &Game) -> Vec<&Player> {
+        let place = game.players.iter().map(|p| p.place).min().unwrap_or(0);
+        game.players.iter().filter(|p| p.place == place).collect()
+    }
+
+    /// Odds by our strength against the enemies near our units, the damage is converted from
+    /// kills by our kills to damage ratio
+    pub fn odds(
+        &self,
+        game: &Game,
+        pp: &PotentialField,
+        enemies: &[&TrackedEnemy],
+        constants: &Constants,
+    ) -> FightOdds {
+        let team = game.units.iter().filter(|u| u.player_id == game.my_id);
+        let ours = team
+            .clone()
+            .map(|u| BehaviourMachine::strength(pp, u))
+            .sum::<f64>();
+        let theirs = enemies
+            .iter()
+            .filter(|e| {
+                team.clone()
+                    .any(|u| u.position.distance_to(&e.unit.position) <= constants.view_distance)
+            })
+            .map(|e| BehaviourMachine::strength(pp, &e.unit) * e.confidence)
+            .sum::<f64>();
+        let (kill_probability, death_probability) = if theirs > 0.0 {
+            let win = ours / (ours + theirs);
+            (win, 1.0 - win)
+        } else {
+            (self.kill_probability, self.death_probability)
+        };
+
+        let hit_points = constants.unit_health + constants.max_shield;
+        let kill_rate = match game.players.iter().find(|p| p.id == game.my_id) {
+            Some(me) if me.damage > 0.0 => {
+                (me.kills as f64 * hit_points / me.damage).clamp(0.1, 1.0)
+            }
+            _ => self.kill_rate,
+        };
+
+        FightOdds {
+            kill_probability,
+            death_probability,
+            damage_fraction: (kill_probability / kill_rate).min(1.0),
+        }
+    }
+
+    /// Score a fight brings: the kill and the damage dealt
+    pub fn fight_value(&self, odds: &FightOdds, constants: &Constants) -> f64 {
+        odds.kill_probability * constants.kill_score
+            + constants.damage_score_multiplier
+                * (constants.unit_health + constants.max_shield)
+                * odds.damage_fraction
+    }
+
+    /// Placement score at stake if the fight goes wrong, units who respawn don't risk it
+    pub fn survival_value(
+        &self,
+        game: &Game,
+        odds: &FightOdds,
+        life_risk: &LifeRisk,
+        constants: &Constants,
+    ) -> f64 {
+        let other_teams = Self::alive_players(game)
+            .iter()
+            .filter(|p| p.id != game.my_id)
+            .count();
+        let team = game.units.iter().filter(|u| u.player_id == game.my_id);
+        let last_lives = team
+            .clone()
+            .filter(|u| !life_risk.will_respawn(u, &game.zone, constants))
+            .count();
+        let at_stake = match team.count() {
+            0 => 1.0,
+            size => last_lives as f64 / size as f64,
+        };
+        at_stake
+            * odds.death_probability
+            * constants.score_per_place
+            * other_teams as f64
+            * self.outlive_fraction
+    }
+
+    /// 0 is playing as safe as possible, 1 is taking every fight, 0.5 is neutral
+    pub fn aggression(
+        &self,
+        game: &Game,
+        pp: &PotentialField,
+        enemies: &[&TrackedEnemy],
+        life_risk: &LifeRisk,
+        constants: &Constants,
+    ) -> f64 {
+        let odds = self.odds(game, pp, enemies, constants);
+        let fight_value = self.fight_value(&odds, constants);
+        let survival_value = self.survival_value(game, &odds, life_risk, constants);
+        let base = if fight_value + survival_value > 0.0 {
+            fight_value / (fight_value + survival_value)
+        } else {
+            0.5
+        };
+
+        // teams behind the leader need points, the leader needs to keep them
+        let my_score = game
+            .players
+            .iter()
+            .find(|p| p.id == game.my_id)
+            .map_or(0.0, |p| p.score);
+        let best_other = game
+            .players
+            .iter()
+            .filter(|p| p.id != game.my_id)
+            .map(|p| p.score)
+            .fold(None, |best: Option<f64>, score| {
+                Some(best.map_or(score, |b| b.max(score)))
+            });
+        let standings = match best_other {
+            Some(best_other) => ((best_other - my_score) / fight_value.max(1.0)).tanh(),
+            None => 0.0,
+        };
+
+        (base + self.standings_weight * standings).clamp(0.0, 1.0)
+    }
+}
@@ -0,0 +1,223 @@
+use crate::model::*;
+use crate::potential_field::{Behaviour, PotentialField, TrackedEnemy};
+use std::collections::HashMap;
+
+#[derive(Copy, Clone, Debug)]
+struct UnitState {
+    behaviour: Behaviour,
+    since_tick: i32,
+}
+
+/// Chooses the behaviour of every unit. Thresholds are split into entry and exit values,
+/// and a state is kept for some ticks unless a more urgent one is needed, so units don't flicker.
+#[derive(Clone, Debug)]
+pub struct BehaviourMachine {
+    /// Ticks before switching to a state which is not more urgent than the current one
+    pub min_ticks_in_state: i32,
+    /// Seconds ahead of the zone forecast
+    pub zone_horizon: f64,
+    /// Distance to the forecasted zone border to start and to stop repositioning, in unit radii
+    pub reposition_enter_margin: f64,
+    pub reposition_exit_margin: f64,
+    /// Part of the health to start and to stop retreating at
+    pub retreat_enter_health: f64,
+    pub retreat_exit_health: f64,
+    /// Part of the max shield to start and to stop healing at
+    pub heal_enter_shield: f64,
+    pub heal_exit_shield: f64,
+    /// Our strength relative to the strongest enemy to start and to stop engaging at, remembered
+    /// enemies count with their confidence
+    pub engage_enter_ratio: f64,
+    pub engage_exit_ratio: f64,
+    /// Readiness of the team to fight from 0 to 1, scales the retreat and engage thresholds.
+    /// At 0.5 they are used as is, every unit can shift it by its own risk.
+    pub aggression: f64,
+    /// Print state transitions to stderr, enabled by `AI_CUP_LOG_BEHAVIOUR`
+    pub log: bool,
+    states: HashMap<i32, UnitState>,
+}
+
+impl Default for BehaviourMachine {
+    fn default() -> Self {
+        Self {
+            min_ticks_in_state: 30,
+            zone_horizon: 3.0,
+            reposition_enter_margin: 2.0,
+            reposition_exit_margin: 6.0,
+            retreat_enter_health: 0.35,
+            retreat_exit_health: 0.7,
+            heal_enter_shield: 0.5,
+            heal_exit_shield: 0.9,
+            engage_enter_ratio: 1.0,
+            engage_exit_ratio: 0.7,
+            aggression: 0.5,
+            log: std::env::var("AI_CUP_LOG_BEHAVIOUR").is_ok(),
+            states: HashMap::new(),
+        }
+    }
+}
+
+/// Upper bound of the retreat health scaled by caution, so full health always ends a retreat
+const MAX_RETREAT_HEALTH: f64 = 0.95;
+
+/// Enemies which are close enough to fight with
+fn enemies_around<'a>(
+    me: &Unit,
+    enemies: &[&'a TrackedEnemy],
+    constants: &Constants,
+) -> Vec<&'a TrackedEnemy> {
+    let distance = constants.view_distance + constants.unit_radius * 2.0;
+    enemies
+        .iter()
+        .filter(|e| e.unit.position.distance_to(&me.position) <= distance)
+        .cloned()
+        .collect()
+}
+
+impl BehaviourMachine {
+    /// Weapon tier scaled by the part of health and shield left, 0 without ammo
+    pub fn strength(pp: &PotentialField, unit: &Unit) -> f64 {
+        let constants = pp.constants();
+        match unit.weapon {
+            Some(weapon) if unit.ammo[weapon as usize] > 0 => {
+                pp.loot_utility.tier(weapon) * (unit.health + unit.shield)
+                    / (constants.unit_health + constants.max_shield)
+            }
+            _ => 0.0,
+        }
+    }
+
+    fn desired(
+        &self,
+        current: Option<Behaviour>,
+        pp: &PotentialField,
+        me: &Unit,
+        enemies: &[&TrackedEnemy],
+        aggression_shift: f64,
+    ) -> Behaviour {
+        let constants = pp.constants();
+        let zone = pp.zone();
+        let is = |behaviour| current == Some(behaviour);
+        if me.remaining_spawn_time.is_some() {
+            return Behaviour::Spawn;
+        }
+
+        let margin = if is(Behaviour::Reposition) {
+            self.reposition_exit_margin
+        } else {
+            self.reposition_enter_margin
+        };
+        let safe_radius = zone.radius_after(self.zone_horizon, constants);
+        if me.position.distance_to(&zone.current_center)
+            > safe_radius - margin * constants.unit_radius
+        {
+            return Behaviour::Reposition;
+        }
+
+        let around = enemies_around(me, enemies, constants);
+        let my_strength = Self::strength(pp, me);
+        let enemy_strength = around
+            .iter()
+            .map(|e| Self::strength(pp, &e.unit) * e.confidence)
+            .fold(0.0, f64::max);
+
+        let caution = 1.5 - (self.aggression + aggression_shift).clamp(0.0, 1.0);
+        let health = me.health / constants.unit_health;
+        let retreat_health = (caution
+            * if is(Behaviour::Retreat) {
+                self.retreat_exit_health
+            } else {
+                self.retreat_enter_health
+            })
+        .min(MAX_RETREAT_HEALTH);
+        if enemy_strength > 0.0 && (health < retreat_health || my_strength == 0.0) {
+            return Behaviour::Retreat;
+        }
+
+        let shield = me.shield / constants.max_shield;
+        // Above this part no potion fits into the shield, so the potion policy won't drink
+        let drinkable_shield =
+            (constants.max_shield - constants.shield_per_potion).max(0.0) / constants.max_shield;
+        let heal_shield = if is(Behaviour::Heal) {
+            self.heal_exit_shield
+        } else {
+            self.heal_enter_shield
+        }
+        .min(drinkable_shield);
+        if me.shield_potions > 0 && shield < heal_shield && !pp.is_in_danger(me) {
+            return Behaviour::Heal;
+        }
+
+        if around.is_empty() || my_strength == 0.0 {
+            return Behaviour::Loot;
+        }
+        let engage_ratio = caution
+            * if is(Behaviour::Engage) {
+                self.engage_exit_ratio
+            } else {
+                self.engage_enter_ratio
+            };
+        if enemy_strength == 0.0 || my_strength / enemy_strength >= engage_ratio {
+            Behaviour::Engage
+        } else {
+            Behaviour::Kite
+        }
+    }
+
+    /// Lower is more urgent
+    fn urgency(behaviour: Behaviour) -> usize {
+        Behaviour::ALL
+            .iter()
+            .position(|&b| b == behaviour)
+            .unwrap_or(Behaviour::ALL.len())
+    }
+
+    pub fn update(
+        &mut self,
+        pp: &PotentialField,
+        me: &Unit,
+        enemies: &[&TrackedEnemy],
+        aggression_shift: f64,
+        current_tick: i32,
+    ) -> Behaviour {
+        let current = self.states.get(&me.id).cloned();
+        let desired = self.desired(
+            current.map(|s| s.behaviour),
+            pp,
+            me,
+            enemies,
+            aggression_shift,
+        );
+
+        let state = match current {
+            Some(state) if state.behaviour == desired => state,
+            Some(state)
+                if current_tick - state.since_tick < self.min_ticks_in_state
+                    && Self::urgency(desired) >= Self::urgency(state.behaviour) =>
+            {
+                state
+            }
+            _ => {
+                if self.log {
+                    eprintln!(
+                        "tick {}: unit {} {} -> {}",
+                        current_tick,
+                        me.id,
+                        current.map_or("None", |s| s.behaviour.name()),
+                        desired
+                    );
+                }
+                UnitState {
+                    behaviour: desired,
+                    since_tick: current_tick,
+                }
+            }
+        };
+        self.states.insert(me.id, state);
+        state.behaviour
+    }
+
+    pub fn get(&self, unit_id: i32) -> Option<Behaviour> {
+        self.states.get(&unit_id).map(|s| s.behaviour)
+    }
+}
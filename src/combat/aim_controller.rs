@@ -0,0 +1,138 @@
+use crate::combat::Intercept;
+use crate::model::*;
+use crate::potential_field::normalize_angle;
+
+/// What to do with the aim this tick
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AimDecision {
+    /// Don't aim and move at full speed
+    Release,
+    /// Keep aiming without shooting
+    Hold,
+    /// Shoot now
+    Fire,
+}
+
+/// Whether shots are good enough to fire according to the hit probability model
+#[derive(Copy, Clone, Debug)]
+pub struct ShotQuality {
+    /// Shooting in the current direction
+    pub now: bool,
+    /// Shooting after turning to the intercept point
+    pub aimed: bool,
+}
+
+/// Decides when to start, hold or release aim, so that full aim is reached when the weapon is
+/// ready and the unit isn't slowed down by aiming for nothing
+#[derive(Clone, Debug)]
+pub struct AimController {
+    /// Aim is released if no shot is possible sooner than this (seconds)
+    pub sprint_horizon: f64,
+    /// Aim is released while retreating if no shot is possible sooner than this (seconds)
+    pub retreat_horizon: f64,
+}
+
+impl Default for AimController {
+    fn default() -> Self {
+        Self {
+            sprint_horizon: 1.0,
+            retreat_horizon: 0.3,
+        }
+    }
+}
+
+impl AimController {
+    /// Seconds until the unit is able to shoot at the intercept point
+    pub fn time_to_shot(
+        &self,
+        me: &Unit,
+        intercept: &Intercept,
+        current_tick: i32,
+        constants: &Constants,
+    ) -> f64 {
+        let weapon = match me.weapon {
+            Some(weapon) => &constants.weapons[weapon as usize],
+            None => return f64::INFINITY,
+        };
+        let full_aim = weapon.aim_time * (1.0 - me.aim);
+        let reload = (me.next_shot_tick - current_tick).max(0) as f64 / constants.ticks_per_second;
+        let angle = normalize_angle((intercept.point - me.position).angle() - me.direction.angle())
+            .abs()
+            .to_degrees();
+        let rotation_speed = if me.aim > 0.0 {
+            weapon.aim_rotation_speed
+        } else {
+            constants.rotation_speed
+        };
+
+        full_aim.max(reload).max(angle / rotation_speed)
+    }
+
+    pub fn decide(
+        &self,
+        me: &Unit,
+        intercept: &Intercept,
+        quality: ShotQuality,
+        retreating: bool,
+        current_tick: i32,
+        constants: &Constants,
+    ) -> AimDecision {
+        let weapon = match me.weapon {
+            Some(weapon) if me.ammo[weapon as usize] > 0 => &constants.weapons[weapon as usize],
+            _ => return AimDecision::Release,
+        };
+
+        let reloaded = me.next_shot_tick <= current_tick;
+        // the aim grows during this tick before the shot is made
+        let aimed_this_tick = me.aim + 1.0 / (weapon.aim_time * constants.ticks_per_second) >= 1.0;
+        if aimed_this_tick && reloaded && quality.now {
+            return AimDecision::Fire;
+        }
+        if !quality.aimed || !intercept.in_range {
+            return AimDecision::Release;
+        }
+
+        let time_to_shot = self.time_to_shot(me, intercept, current_tick, constants);
+        let horizon = if retreating {
+            self.retreat_horizon
+        } else {
+            self.sprint_horizon
+        };
+        if time_to_shot > horizon {
+            return AimDecision::Release;
+        }
+
+        // start aiming just in time to be in full aim when reloaded
+        let full_aim = weapon.aim_time * (1.0 - me.aim);
+        let reload = (me.next_shot_tick - current_tick).max(0) as f64 / constants.ticks_per_second;
+        if me.aim == 0.0 && reload > full_aim + 1.0 / constants.ticks_per_second {
+            return AimDecision::Release;
+        }
+
+        AimDecision::Hold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fires_on_the_tick_aim_completes() {
+        let constants = test_constants();
+        let controller = AimController::default();
+        let target = test_unit(2, 1, Vec2::new(20.0, 0.0));
+        let mut me = test_unit(1, 0, Vec2::zero());
+        let intercept = Intercept::direct(&me, &target, &constants);
+        let quality = ShotQuality {
+            now: true,
+            aimed: true,
+        };
+        let decide = |me: &Unit| controller.decide(me, &intercept, quality, false, 0, &constants);
+
+        me.aim = 0.9;
+        assert_eq!(decide(&me), AimDecision::Hold);
+        me.aim = 0.97;
+        assert_eq!(decide(&me), AimDecision::Fire);
+    }
+}
@@ -0,0 +1,206 @@
+use crate::combat::Intercept;
+use crate::model::*;
+use crate::potential_field::normalize_angle;
+
+/// Number of rays across the target's width used to estimate occlusion
+const OCCLUSION_RAYS: usize = 7;
+
+/// Estimates the probability that a shot hits the target
+#[derive(Clone, Debug)]
+pub struct HitProbabilityModel {
+    /// How much the target's ability to step aside lowers the probability (0 - target never
+    /// dodges, 1 - target always dodges the best way)
+    pub evasion_weight: f64,
+    /// Minimal probability to shoot with full inventory of ammo
+    pub min_probability: f64,
+    /// Minimal probability to shoot with the last ammo
+    pub scarce_min_probability: f64,
+}
+
+impl Default for HitProbabilityModel {
+    fn default() -> Self {
+        Self {
+            evasion_weight: 0.5,
+            min_probability: 0.3,
+            scarce_min_probability: 0.6,
+        }
+    }
+}
+
+impl HitProbabilityModel {
+    /// Part of the target width which is not hidden behind obstacles or allies
+    fn visible_fraction(
+        &self,
+        me: &Unit,
+        aim_point: Vec2,
+        allies: &[&Unit],
+        constants: &Constants,
+    ) -> f64 {
+        let across = (aim_point - me.position)
+            .normalize()
+            .rotate(std::f64::consts::PI / 2.0);
+        let visible = (0..OCCLUSION_RAYS)
+            .filter(|&i| {
+                let k = i as f64 / (OCCLUSION_RAYS - 1) as f64 * 2.0 - 1.0;
+                let point = aim_point + across * (k * constants.unit_radius);
+                let line = Line::new(me.position, point);
+                !constants
+                    .obstacles
+                    .iter()
+                    .filter(|o| !o.can_shoot_through)
+                    .any(|o| o.as_circle(0.0).intercept_with_line(&line))
+                    && !allies.iter().filter(|u| u.id != me.id).any(|u| {
+                        u.as_circle(constants.unit_radius)
+                            .intercept_with_line(&line)
+                    })
+            })
+            .count();
+
+        visible as f64 / OCCLUSION_RAYS as f64
+    }
+
+    /// Probability to hit the target at the intercept point shooting in `direction`. Shots
+    /// spread uniformly inside the weapon's spread angle.
+    pub fn probability(
+        &self,
+        me: &Unit,
+        target: &Unit,
+        intercept: &Intercept,
+        direction: Vec2,
+        allies: &[&Unit],
+        constants: &Constants,
+    ) -> f64 {
+        let weapon = match me.weapon {
+            Some(weapon) => &constants.weapons[weapon as usize],
+            None => return 0.0,
+        };
+        if target.remaining_spawn_time.unwrap_or(0.0) >= intercept.time {
+            // invulnerable until it spawns
+            return 0.0;
+        }
+        let aim_point = intercept.point;
+        let vec = aim_point - me.position;
+        let distance = vec.length();
+        if distance - constants.unit_radius > weapon.range() {
+            return 0.0;
+        }
+
+        let radius = constants.unit_radius;
+        let half_width = if distance <= radius {
+            std::f64::consts::FRAC_PI_2
+        } else {
+            (radius / distance).asin()
+        };
+        let offset = normalize_angle(vec.angle() - direction.angle());
+        let half_spread = weapon.spread.to_radians() / 2.0;
+        let cone = if half_spread == 0.0 {
+            if offset.abs() <= half_width {
+                1.0
+            } else {
+                0.0
+            }
+        } else {
+            let overlap =
+                (offset + half_width).min(half_spread) - (offset - half_width).max(-half_spread);
+            (overlap.max(0.0) / (2.0 * half_spread)).min(1.0)
+        };
+
+        let evasion = radius / (radius + intercept.evasion_radius * self.evasion_weight);
+
+        cone * evasion * self.visible_fraction(me, aim_point, allies, constants)
+    }
+
+    /// Probability to hit the target after turning exactly to the intercept point
+    pub fn probability_when_aimed(
+        &self,
+        me: &Unit,
+        target: &Unit,
+        intercept: &Intercept,
+        allies: &[&Unit],
+        constants: &Constants,
+    ) -> f64 {
+        let direction = intercept.point - me.position;
+        self.probability(me, target, intercept, direction, allies, constants)
+    }
+
+    /// The less ammo is left the better the shot should be
+    pub fn should_shoot(&self, probability: f64, me: &Unit, constants: &Constants) -> bool {
+        let weapon = match me.weapon {
+            Some(weapon) => weapon as usize,
+            None => return false,
+        };
+        let ammo = me.ammo[weapon] as f64;
+        let max_ammo = constants.weapons[weapon].max_inventory_ammo.max(1) as f64;
+        let scarcity = 1.0 - (ammo / max_ammo).min(1.0);
+        let threshold =
+            self.min_probability + (self.scarce_min_probability - self.min_probability) * scarcity;
+
+        probability >= threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aimed(distance: f64, constants: &Constants) -> f64 {
+        let mut me = test_unit(1, 1, Vec2::zero());
+        me.weapon = Some(STAFF);
+        let target = test_unit(2, 2, Vec2::new(distance, 0.0));
+        let intercept = Intercept::direct(&me, &target, constants);
+        HitProbabilityModel::default().probability_when_aimed(
+            &me,
+            &target,
+            &intercept,
+            &[],
+            constants,
+        )
+    }
+
+    #[test]
+    fn probability_falls_with_distance() {
+        let constants = test_constants();
+        let near = aimed(3.0, &constants);
+        let far = aimed(15.0, &constants);
+        assert_eq!(near, 1.0);
+        assert!(far > 0.0 && far < near);
+        assert_eq!(aimed(25.0, &constants), 0.0);
+    }
+
+    #[test]
+    fn probability_is_zero_behind_obstacle() {
+        let mut constants = test_constants();
+        constants.obstacles.push(Obstacle {
+            id: 0,
+            position: Vec2::new(10.0, 0.0),
+            radius: 3.0,
+            can_see_through: false,
+            can_shoot_through: false,
+        });
+        assert_eq!(aimed(15.0, &constants), 0.0);
+    }
+
+    #[test]
+    fn probability_is_zero_without_weapon() {
+        let constants = test_constants();
+        let mut me = test_unit(1, 1, Vec2::zero());
+        me.weapon = None;
+        let target = test_unit(2, 2, Vec2::new(5.0, 0.0));
+        let intercept = Intercept::direct(&me, &target, &constants);
+        let model = HitProbabilityModel::default();
+        let probability = model.probability_when_aimed(&me, &target, &intercept, &[], &constants);
+        assert_eq!(probability, 0.0);
+    }
+
+    #[test]
+    fn scarce_ammo_needs_better_shot() {
+        let constants = test_constants();
+        let model = HitProbabilityModel::default();
+        let mut me = test_unit(1, 1, Vec2::zero());
+        me.ammo = vec![0, 0, 100];
+        assert!(model.should_shoot(0.4, &me, &constants));
+        me.ammo = vec![0, 0, 1];
+        assert!(!model.should_shoot(0.4, &me, &constants));
+        assert!(model.should_shoot(0.7, &me, &constants));
+    }
+}
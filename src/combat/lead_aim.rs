@@ -0,0 +1,213 @@
+use crate::model::*;
+
+/// Point where a projectile meets a moving target
+#[derive(Clone, Debug)]
+pub struct Intercept {
+    /// Predicted position of the target center at the moment of hit
+    pub point: Vec2,
+    /// Seconds of projectile flight
+    pub time: f64,
+    /// Whether the projectile lives long enough to get there
+    pub in_range: bool,
+    /// How far the target can move away from the point by accelerating in another direction
+    pub evasion_radius: f64,
+}
+
+impl Intercept {
+    /// Aiming right at the target without any lead
+    pub fn direct(me: &Unit, target: &Unit, constants: &Constants) -> Self {
+        let distance = (target.position.distance_to(&me.position) - constants.unit_radius).max(0.0);
+        let (time, in_range) = match me.weapon {
+            Some(weapon) => {
+                let weapon = &constants.weapons[weapon as usize];
+                let time = distance / weapon.projectile_speed;
+                (time, time <= weapon.projectile_life_time)
+            }
+            None => (f64::INFINITY, false),
+        };
+
+        Self {
+            point: target.position,
+            time,
+            in_range,
+            evasion_radius: 0.0,
+        }
+    }
+}
+
+/// Smallest positive time when a projectile fired from `shooter` meets the target moving with
+/// constant velocity, or None if the target runs away faster than the projectile
+pub fn intercept_time(
+    shooter: Vec2,
+    projectile_speed: f64,
+    target_position: Vec2,
+    target_velocity: Vec2,
+) -> Option<f64> {
+    let d = target_position - shooter;
+    let a = target_velocity.dot(&target_velocity) - projectile_speed * projectile_speed;
+    let b = 2.0 * d.dot(&target_velocity);
+    let c = d.dot(&d);
+
+    if a.abs() < 1e-9 {
+        if b >= 0.0 {
+            return None;
+        }
+        return Some(-c / b);
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let sqrt = discriminant.sqrt();
+    let t1 = (-b - sqrt) / (2.0 * a);
+    let t2 = (-b + sqrt) / (2.0 * a);
+
+    [t1, t2]
+        .into_iter()
+        .filter(|t| *t >= 0.0)
+        .min_by(|a, b| a.total_cmp(b))
+}
+
+/// Average velocity over given seconds of a unit changing its velocity to the target one
+/// with limited acceleration
+fn average_velocity(velocity: Vec2, target: Vec2, acceleration: f64, seconds: f64) -> Vec2 {
+    let delta = target - velocity;
+    let change_time = delta.length() / acceleration;
+    if seconds <= 0.0 || change_time == 0.0 {
+        return velocity;
+    }
+    if change_time >= seconds {
+        velocity + delta.normalize() * (acceleration * seconds / 2.0)
+    } else {
+        velocity + delta * (1.0 - change_time / (2.0 * seconds))
+    }
+}
+
+/// Lead point for shooting at the target with the unit's weapon. With `limit_acceleration` the
+/// target reaches the velocity it can actually move with only at `unit_acceleration`, and the
+/// evasion radius is estimated from its acceleration.
+pub fn lead_aim(
+    me: &Unit,
+    target: &Unit,
+    constants: &Constants,
+    limit_acceleration: bool,
+) -> Option<Intercept> {
+    let weapon = &constants.weapons[me.weapon? as usize];
+    // projectile hits when it touches the unit, not the center
+    let hit_time = |velocity: Vec2| {
+        intercept_time(
+            me.position,
+            weapon.projectile_speed,
+            target.position,
+            velocity,
+        )
+        .map(|time| (time - constants.unit_radius / weapon.projectile_speed).max(0.0))
+    };
+
+    let mut velocity = target.velocity;
+    let mut time = hit_time(velocity)?;
+    if limit_acceleration {
+        let reachable = target.clamp_velocity(target.velocity, constants);
+        // flight time depends on the displacement, a few iterations are enough to converge
+        for _ in 0..3 {
+            velocity = average_velocity(
+                target.velocity,
+                reachable,
+                constants.unit_acceleration,
+                time,
+            );
+            time = hit_time(velocity)?;
+        }
+    }
+    let evasion_radius = if limit_acceleration {
+        let max_speed = constants.max_unit_forward_speed;
+        let acceleration_distance = constants.unit_acceleration * time * time / 2.0;
+        acceleration_distance.min(max_speed * 2.0 * time)
+    } else {
+        0.0
+    };
+
+    Some(Intercept {
+        point: target.position + velocity * time,
+        time,
+        in_range: time <= weapon.projectile_life_time,
+        evasion_radius,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intercept_time_of_standing_target() {
+        let time = intercept_time(Vec2::zero(), 10.0, Vec2::new(30.0, 40.0), Vec2::zero());
+        assert!((time.unwrap() - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn intercept_time_of_crossing_target() {
+        let target = Vec2::new(30.0, 0.0);
+        let velocity = Vec2::new(0.0, 40.0);
+        let time = intercept_time(Vec2::zero(), 50.0, target, velocity).unwrap();
+        let point = target + velocity * time;
+        assert!((point.length() - 50.0 * time).abs() < 1e-9);
+        assert!((time - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn intercept_time_of_target_running_away_faster() {
+        let time = intercept_time(
+            Vec2::zero(),
+            5.0,
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, 0.0),
+        );
+        assert!(time.is_none());
+    }
+
+    #[test]
+    fn lead_point_is_where_the_target_is_at_hit_time() {
+        let constants = test_constants();
+        let me = test_unit(1, 1, Vec2::zero());
+        let mut target = test_unit(2, 2, Vec2::new(20.0, 0.0));
+        target.velocity = Vec2::new(0.0, 5.0);
+        let intercept = lead_aim(&me, &target, &constants, false).unwrap();
+        let expected = target.position + target.velocity * intercept.time;
+        assert!((intercept.point - expected).length() < 1e-9);
+        let flight = intercept.point.length() - constants.unit_radius;
+        assert!((flight / 60.0 - intercept.time).abs() < 0.01);
+    }
+
+    #[test]
+    fn too_fast_target_is_led_less() {
+        let constants = test_constants();
+        let me = test_unit(1, 1, Vec2::zero());
+        let mut target = test_unit(2, 2, Vec2::new(20.0, 0.0));
+        target.direction = Vec2::new(0.0, 1.0);
+        target.velocity = Vec2::new(0.0, 20.0);
+        let free = lead_aim(&me, &target, &constants, false).unwrap();
+        let limited = lead_aim(&me, &target, &constants, true).unwrap();
+        assert!(limited.point.y < free.point.y);
+        assert!(limited.point.y > 10.0 * limited.time);
+    }
+
+    #[test]
+    fn intercept_time_with_equal_speeds() {
+        let approaching = intercept_time(
+            Vec2::zero(),
+            10.0,
+            Vec2::new(20.0, 0.0),
+            Vec2::new(-10.0, 0.0),
+        );
+        assert!((approaching.unwrap() - 1.0).abs() < 1e-9);
+        let leaving = intercept_time(
+            Vec2::zero(),
+            10.0,
+            Vec2::new(20.0, 0.0),
+            Vec2::new(10.0, 0.0),
+        );
+        assert!(leaving.is_none());
+    }
+}
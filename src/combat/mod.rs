@@ -0,0 +1,13 @@
+mod aim_controller;
+mod hit_probability;
+mod lead_aim;
+mod potion_policy;
+mod target_scorer;
+mod team_coordinator;
+
+pub use aim_controller::*;
+pub use hit_probability::*;
+pub use lead_aim::*;
+pub use potion_policy::*;
+pub use target_scorer::*;
+pub use team_coordinator::*;
@@ -0,0 +1,102 @@
+use crate::model::*;
+use crate::potential_field::{line_of_fire_danger, PotentialField};
+
+/// What to do with shield potions this tick
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PotionDecision {
+    /// Start or continue drinking
+    Drink,
+    /// Don't drink now
+    Skip,
+    /// Stop drinking which is already in progress
+    Cancel,
+}
+
+/// Drinks potions only when the unit is likely to finish without being hit
+#[derive(Clone, Debug)]
+pub struct PotionPolicy {
+    /// Max expected damage during drinking relative to unit health to start drinking
+    pub max_start_risk: f64,
+    /// Expected damage relative to unit health which cancels drinking in progress
+    pub cancel_risk: f64,
+    /// Risk multiplier while health regeneration is delayed by recent damage
+    pub recently_damaged_factor: f64,
+}
+
+impl Default for PotionPolicy {
+    fn default() -> Self {
+        Self {
+            max_start_risk: 0.1,
+            cancel_risk: 0.3,
+            recently_damaged_factor: 2.0,
+        }
+    }
+}
+
+impl PotionPolicy {
+    /// Seconds left to finish drinking, full use time if not drinking yet
+    pub fn remaining_time(&self, me: &Unit, current_tick: i32, constants: &Constants) -> f64 {
+        match &me.action {
+            Some(action) if action.action_type == ActionType::UseShieldPotion => {
+                (action.finish_tick - current_tick).max(0) as f64 / constants.ticks_per_second
+            }
+            _ => constants.shield_potion_use_time,
+        }
+    }
+
+    /// Expected damage relative to unit health while standing still for given seconds
+    pub fn risk(&self, me: &Unit, pp: &PotentialField, seconds: f64, current_tick: i32) -> f64 {
+        let constants = pp.constants();
+        let circle = me.as_circle(constants.unit_radius);
+        let projectiles = pp
+            .dangerous_projectiles
+            .iter()
+            .filter(|p| p.shooter_player_id != me.player_id || constants.friendly_fire)
+            .filter(|p| p.hits_circle_within(&circle, seconds.min(p.life_time)))
+            .map(|p| constants.weapons[p.weapon_type_index as usize].projectile_damage)
+            .sum::<f64>()
+            / constants.unit_health;
+
+        let enemies = pp
+            .enemies
+            .iter()
+            .map(|enemy| line_of_fire_danger(enemy, me.position, constants) * seconds)
+            .sum::<f64>();
+
+        let risk = projectiles + enemies;
+        if current_tick < me.health_regeneration_start_tick {
+            risk * self.recently_damaged_factor
+        } else {
+            risk
+        }
+    }
+
+    pub fn decide(&self, me: &Unit, pp: &PotentialField, current_tick: i32) -> PotionDecision {
+        let constants = pp.constants();
+        let seconds = self.remaining_time(me, current_tick, constants);
+        let risk = self.risk(me, pp, seconds, current_tick);
+
+        let drinking = matches!(
+            &me.action,
+            Some(action) if action.action_type == ActionType::UseShieldPotion
+        );
+        if drinking {
+            return if risk > self.cancel_risk {
+                PotionDecision::Cancel
+            } else {
+                PotionDecision::Drink
+            };
+        }
+
+        let needs_shield = me.shield <= constants.max_shield - constants.shield_per_potion;
+        if me.action.is_none()
+            && needs_shield
+            && me.shield_potions > 0
+            && risk <= self.max_start_risk
+        {
+            PotionDecision::Drink
+        } else {
+            PotionDecision::Skip
+        }
+    }
+}
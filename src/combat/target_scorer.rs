@@ -0,0 +1,122 @@
+use crate::combat::{lead_aim, HitProbabilityModel, Intercept};
+use crate::model::*;
+use crate::potential_field::{threat_strength, TrackedEnemy};
+
+/// Enemy with the estimate of how good it is as a target
+#[derive(Clone, Debug)]
+pub struct ScoredTarget<'a> {
+    pub enemy: &'a Unit,
+    /// Where to aim to hit the enemy
+    pub intercept: Intercept,
+    /// Probability to hit the enemy after aiming at the intercept point
+    pub hit_probability: f64,
+    /// Shots needed to kill the enemy
+    pub shots_to_kill: i32,
+    /// Seconds needed to kill the enemy taking misses into account
+    pub time_to_kill: f64,
+    /// Damage per second the enemy can deal to us, relative to our health, weighted by the
+    /// confidence that the enemy is there and by how far out of their range we are
+    pub threat: f64,
+    pub score: f64,
+}
+
+/// Ranks enemies by how profitable it is to shoot at them
+#[derive(Clone, Debug)]
+pub struct TargetScorer {
+    /// Multiplier of the score of enemies which need just one more hit
+    pub finishing_bonus: f64,
+    /// How much the enemy's threat to us increases their priority
+    pub threat_weight: f64,
+    pub hit_model: HitProbabilityModel,
+}
+
+impl Default for TargetScorer {
+    fn default() -> Self {
+        Self {
+            finishing_bonus: 2.0,
+            threat_weight: 1.0,
+            hit_model: HitProbabilityModel::default(),
+        }
+    }
+}
+
+impl TargetScorer {
+    fn score_target<'a>(
+        &self,
+        me: &Unit,
+        tracked: &'a TrackedEnemy,
+        allies: &[&Unit],
+        constants: &Constants,
+    ) -> ScoredTarget<'a> {
+        let enemy = &tracked.unit;
+        let weapon = &constants.weapons[me.weapon.unwrap() as usize];
+        let distance = enemy.position.distance_to(&me.position);
+        let intercept = lead_aim(me, enemy, constants, true)
+            .unwrap_or_else(|| Intercept::direct(me, enemy, constants));
+        let hit_probability = if intercept.in_range {
+            self.hit_model
+                .probability_when_aimed(me, enemy, &intercept, allies, constants)
+        } else {
+            0.0
+        };
+
+        let hit_points = enemy.health + enemy.shield;
+        let shots_to_kill = (hit_points / weapon.projectile_damage).ceil().max(1.0) as i32;
+        let aim_time = weapon.aim_time * (1.0 - me.aim);
+        let time_to_kill = if hit_probability > 0.0 {
+            aim_time
+                + intercept.time
+                + (shots_to_kill as f64 / hit_probability - 1.0) / weapon.rounds_per_second
+        } else {
+            f64::INFINITY
+        };
+
+        // enemies out of their range are less of a threat for now
+        let threat = threat_strength(tracked, constants)
+            * enemy.range(constants).map_or(0.0, |range| {
+                ((range + constants.unit_radius) / distance.max(f64::EPSILON)).min(1.0)
+            });
+
+        let mut score = if time_to_kill.is_finite() {
+            (1.0 + threat * self.threat_weight) / (1.0 + time_to_kill)
+        } else {
+            0.0
+        };
+        if shots_to_kill == 1 {
+            score *= self.finishing_bonus;
+        }
+        // enemies we can't shoot now are ordered by distance
+        score += 0.001 / (1.0 + distance);
+        // remembered enemies may be somewhere else already
+        score *= tracked.confidence;
+
+        ScoredTarget {
+            enemy,
+            intercept,
+            hit_probability,
+            shots_to_kill,
+            time_to_kill,
+            threat,
+            score,
+        }
+    }
+
+    /// Best targets first, empty if the unit has no weapon
+    pub fn rank<'a>(
+        &self,
+        me: &Unit,
+        enemies: &[&'a TrackedEnemy],
+        allies: &[&Unit],
+        constants: &Constants,
+    ) -> Vec<ScoredTarget<'a>> {
+        if me.weapon.is_none() {
+            return Vec::new();
+        }
+        let mut targets = enemies
+            .iter()
+            .map(|enemy| self.score_target(me, enemy, allies, constants))
+            .collect::<Vec<ScoredTarget>>();
+        targets.sort_by(|a, b| b.score.total_cmp(&a.score));
+        targets
+    }
+}
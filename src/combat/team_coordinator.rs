@@ -0,0 +1,93 @@
+use crate::combat::{ScoredTarget, TargetScorer};
+use crate::model::*;
+use crate::potential_field::TrackedEnemy;
+use std::collections::HashMap;
+
+/// Assigns targets to all our units at once, so they focus fire on one enemy
+/// until it is surely dead and spread the rest of the fire over other enemies
+#[derive(Clone, Debug)]
+pub struct TeamCoordinator {
+    /// Multiplier of the score of an enemy which teammates shoot at but can't kill alone
+    pub focus_bonus: f64,
+    /// Multiplier of the score of an enemy which teammates kill anyway
+    pub overkill_penalty: f64,
+    /// Seconds of the expected teammates' fire counted against the enemy's hit points
+    pub kill_window: f64,
+}
+
+impl Default for TeamCoordinator {
+    fn default() -> Self {
+        Self {
+            focus_bonus: 1.5,
+            overkill_penalty: 0.2,
+            kill_window: 1.0,
+        }
+    }
+}
+
+impl TeamCoordinator {
+    fn expected_dps(target: &ScoredTarget, shooter: &Unit, constants: &Constants) -> f64 {
+        match shooter.weapon {
+            Some(weapon) => {
+                let weapon = &constants.weapons[weapon as usize];
+                target.hit_probability * weapon.projectile_damage * weapon.rounds_per_second
+            }
+            None => 0.0,
+        }
+    }
+
+    /// Scores every pair of our unit and enemy once, then greedily takes the best pair
+    /// taking the fire of already assigned units into account
+    pub fn assign<'a>(
+        &self,
+        scorer: &TargetScorer,
+        team: &[&Unit],
+        enemies: &[&'a TrackedEnemy],
+        constants: &Constants,
+    ) -> HashMap<i32, ScoredTarget<'a>> {
+        let mut pairs = team
+            .iter()
+            .filter(|u| u.remaining_spawn_time.is_none())
+            .filter(|u| matches!(u.weapon, Some(w) if u.ammo[w as usize] > 0))
+            .flat_map(|u| {
+                scorer
+                    .rank(u, enemies, team, constants)
+                    .into_iter()
+                    .map(move |t| (*u, t))
+            })
+            .collect::<Vec<_>>();
+
+        let mut incoming_dps = HashMap::<i32, f64>::new();
+        let mut assignments = HashMap::new();
+        while !pairs.is_empty() {
+            let adjusted = |target: &ScoredTarget| {
+                let hit_points = target.enemy.health + target.enemy.shield;
+                let incoming = incoming_dps.get(&target.enemy.id).cloned().unwrap_or(0.0);
+                let factor = if incoming * self.kill_window >= hit_points {
+                    self.overkill_penalty
+                } else if incoming > 0.0 {
+                    self.focus_bonus
+                } else {
+                    1.0
+                };
+                target.score * factor
+            };
+            let best = pairs
+                .iter()
+                .enumerate()
+                .max_by(|(_, (_, a)), (_, (_, b))| adjusted(a).total_cmp(&adjusted(b)))
+                .map(|(i, _)| i);
+
+            let (shooter, target) = match best {
+                Some(i) => pairs.swap_remove(i),
+                None => break,
+            };
+            pairs.retain(|(u, _)| u.id != shooter.id);
+            *incoming_dps.entry(target.enemy.id).or_insert(0.0) +=
+                Self::expected_dps(&target, shooter, constants);
+            assignments.insert(shooter.id, target);
+        }
+
+        assignments
+    }
+}
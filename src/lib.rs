@@ -1,6 +1,9 @@
 pub mod trans;
 
+pub mod behaviour;
 pub mod codegame;
+pub mod combat;
 pub mod debugging;
+pub mod looting;
 pub mod model;
 pub mod potential_field;
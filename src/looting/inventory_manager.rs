@@ -0,0 +1,208 @@
+use crate::model::*;
+use std::collections::HashSet;
+
+/// Drop ordered by the unit which is not seen on the ground yet
+#[derive(Clone, Debug)]
+struct PendingDrop {
+    order: ActionOrder,
+    position: Vec2,
+    dropped_by: i32,
+    tick: i32,
+}
+
+impl PendingDrop {
+    fn is_dropped_as(&self, loot: &Loot, constants: &Constants) -> bool {
+        let same_item = match (&self.order, &loot.item) {
+            (
+                ActionOrder::DropAmmo {
+                    weapon_type_index, ..
+                },
+                Item::Ammo {
+                    weapon_type_index: item_weapon,
+                    ..
+                },
+            ) => weapon_type_index == item_weapon,
+            (ActionOrder::DropShieldPotions { .. }, Item::ShieldPotions { .. }) => true,
+            _ => false,
+        };
+        same_item && self.position.distance_to(&loot.position) <= constants.unit_radius
+    }
+}
+
+/// Loot dropped for allies is not picked up back by the unit who dropped it
+#[derive(Clone, Debug)]
+struct Reservation {
+    loot_id: i32,
+    dropped_by: i32,
+    until_tick: i32,
+}
+
+/// Decides when dropping items is worth the looting time
+#[derive(Clone, Debug)]
+pub struct InventoryManager {
+    /// Max distance to the ally we drop items for
+    pub share_distance: f64,
+    /// Ticks during which the unit doesn't pick up what it dropped
+    pub reserve_ticks: i32,
+    /// Ticks to wait for the dropped item to appear on the ground
+    pub confirm_ticks: i32,
+    pending: Vec<PendingDrop>,
+    reservations: Vec<Reservation>,
+    known_loot: HashSet<i32>,
+}
+
+impl Default for InventoryManager {
+    fn default() -> Self {
+        Self {
+            share_distance: 10.0,
+            reserve_ticks: 300,
+            confirm_ticks: 5,
+            pending: Vec::new(),
+            reservations: Vec::new(),
+            known_loot: HashSet::new(),
+        }
+    }
+}
+
+impl InventoryManager {
+    /// Ammo for a weapon we don't hold and don't want, for an ally who holds that weapon
+    fn drop_ammo(
+        &self,
+        me: &Unit,
+        allies: &[&Unit],
+        preferred_weapon: i32,
+        constants: &Constants,
+    ) -> Option<ActionOrder> {
+        (0..constants.weapons.len() as i32)
+            .filter(|&w| me.weapon != Some(w) && w != preferred_weapon && me.ammo[w as usize] > 0)
+            .find_map(|w| {
+                let max_ammo = constants.weapons[w as usize].max_inventory_ammo;
+                let need = allies
+                    .iter()
+                    .filter(|a| a.id != me.id && a.weapon == Some(w))
+                    .filter(|a| a.position.distance_to(&me.position) <= self.share_distance)
+                    .map(|a| max_ammo - a.ammo[w as usize])
+                    .max()?;
+                let amount = need.min(me.ammo[w as usize]);
+                (amount > 0).then_some(ActionOrder::DropAmmo {
+                    weapon_type_index: w,
+                    amount,
+                })
+            })
+    }
+
+    /// Weapon without ammo when the preferred one lies right here
+    fn drop_weapon(
+        &self,
+        me: &Unit,
+        loot: &[&Loot],
+        preferred_weapon: i32,
+        constants: &Constants,
+    ) -> Option<ActionOrder> {
+        let weapon = me.weapon?;
+        if weapon == preferred_weapon || me.ammo[weapon as usize] > 0 {
+            return None;
+        }
+        loot.iter()
+            .filter(|l| l.position.distance_to(&me.position) <= constants.unit_radius)
+            .any(
+                |l| matches!(l.item, Item::Weapon { type_index } if type_index == preferred_weapon),
+            )
+            .then_some(ActionOrder::DropWeapon {})
+    }
+
+    /// Potions for an ally with lower shield and empty inventory when we can't drink ours now
+    fn drop_potions(
+        &self,
+        me: &Unit,
+        allies: &[&Unit],
+        constants: &Constants,
+    ) -> Option<ActionOrder> {
+        if me.shield_potions < 2 || me.shield < constants.max_shield - constants.shield_per_potion {
+            return None;
+        }
+        let ally = allies
+            .iter()
+            .filter(|a| a.id != me.id && a.shield_potions == 0)
+            .filter(|a| a.shield + constants.shield_per_potion <= me.shield)
+            .find(|a| a.position.distance_to(&me.position) <= self.share_distance)?;
+        let amount = (me.shield_potions / 2)
+            .min(constants.max_shield_potions_in_inventory - ally.shield_potions);
+
+        (amount > 0).then_some(ActionOrder::DropShieldPotions { amount })
+    }
+
+    pub fn decide(
+        &self,
+        me: &Unit,
+        allies: &[&Unit],
+        loot: &[&Loot],
+        preferred_weapon: i32,
+        constants: &Constants,
+    ) -> Option<ActionOrder> {
+        if me.action.is_some() {
+            return None;
+        }
+        self.drop_weapon(me, loot, preferred_weapon, constants)
+            .or_else(|| self.drop_ammo(me, allies, preferred_weapon, constants))
+            .or_else(|| self.drop_potions(me, allies, constants))
+    }
+
+    /// Reserves new loot which appeared where our units dropped items and forgets old
+    /// reservations and drops which never happened
+    pub fn update(&mut self, game: &Game, constants: &Constants) {
+        self.reservations
+            .retain(|r| r.until_tick > game.current_tick);
+        for loot in game.loot.iter() {
+            if self.known_loot.contains(&loot.id) {
+                continue;
+            }
+            if let Some(index) = self
+                .pending
+                .iter()
+                .position(|d| d.is_dropped_as(loot, constants))
+            {
+                let drop = self.pending.remove(index);
+                self.reservations.push(Reservation {
+                    loot_id: loot.id,
+                    dropped_by: drop.dropped_by,
+                    until_tick: game.current_tick + self.reserve_ticks,
+                });
+            }
+        }
+        self.known_loot = game.loot.iter().map(|l| l.id).collect();
+        self.pending
+            .retain(|d| game.current_tick - d.tick <= self.confirm_ticks);
+    }
+
+    /// Remembers what was ordered to drop this tick
+    pub fn record(&mut self, order: &Order, game: &Game) {
+        for (unit_id, unit_order) in order.unit_orders.iter() {
+            let drops_for_ally = matches!(
+                unit_order.action,
+                Some(ActionOrder::DropAmmo { .. }) | Some(ActionOrder::DropShieldPotions { .. })
+            );
+            if !drops_for_ally {
+                continue;
+            }
+            if let (Some(unit), Some(action)) = (
+                game.units.iter().find(|u| u.id == *unit_id),
+                &unit_order.action,
+            ) {
+                self.pending.push(PendingDrop {
+                    order: action.clone(),
+                    position: unit.position,
+                    dropped_by: unit.id,
+                    tick: game.current_tick,
+                });
+            }
+        }
+    }
+
+    /// Whether the loot was dropped by the unit for an ally
+    pub fn is_reserved(&self, loot: &Loot, me: &Unit) -> bool {
+        self.reservations
+            .iter()
+            .any(|r| r.loot_id == loot.id && r.dropped_by == me.id)
+    }
+}
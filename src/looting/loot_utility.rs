@@ -0,0 +1,165 @@
+use crate::model::*;
+
+/// How much an item is worth picking up for the unit, 0 if not at all
+#[derive(Clone, Debug)]
+pub struct LootUtility {
+    /// Value of every weapon type by index, the best one is preferred
+    pub weapon_tiers: Vec<f64>,
+    pub ammo_weight: f64,
+    pub potion_weight: f64,
+    /// Part of teammates' needs the item is worth to us, we can drop it for them
+    pub share_weight: f64,
+    /// Ammo and potions are worth that much more when the zone has shrunk completely
+    pub late_game_bonus: f64,
+}
+
+/// Tiers of the wand, the staff and the bow unless `AI_CUP_WEAPON_TIERS` overrides them
+const DEFAULT_WEAPON_TIERS: [f64; 3] = [0.3, 0.6, 1.0];
+
+/// Comma separated tiers by weapon type index, None if any of them is not a number
+fn parse_weapon_tiers(s: &str) -> Option<Vec<f64>> {
+    s.split(',')
+        .map(|tier| tier.trim().parse::<f64>().ok().filter(|t| t.is_finite()))
+        .collect()
+}
+
+impl LootUtility {
+    /// Weapon tiers are taken from `AI_CUP_WEAPON_TIERS`, e.g. `0.3,0.6,1.0`.
+    /// Weapons without a tier are not worth picking up.
+    pub fn new(constants: &Constants) -> Self {
+        let tiers = std::env::var("AI_CUP_WEAPON_TIERS")
+            .ok()
+            .and_then(|s| parse_weapon_tiers(&s))
+            .unwrap_or_else(|| DEFAULT_WEAPON_TIERS.to_vec());
+        let weapon_tiers = (0..constants.weapons.len())
+            .map(|weapon| tiers.get(weapon).cloned().unwrap_or(0.0))
+            .collect();
+        Self {
+            weapon_tiers,
+            ammo_weight: 1.0,
+            potion_weight: 1.0,
+            share_weight: 0.3,
+            late_game_bonus: 1.0,
+        }
+    }
+
+    pub fn tier(&self, weapon: i32) -> f64 {
+        self.weapon_tiers
+            .get(weapon as usize)
+            .cloned()
+            .unwrap_or(0.0)
+    }
+
+    pub fn preferred_weapon(&self) -> i32 {
+        (0..self.weapon_tiers.len() as i32)
+            .max_by(|&a, &b| self.tier(a).total_cmp(&self.tier(b)))
+            .unwrap_or(BOW)
+    }
+
+    /// 0 at the start of the game, 1 when the zone has shrunk completely
+    fn game_phase(zone: &Zone, constants: &Constants) -> f64 {
+        (1.0 - zone.current_radius / constants.initial_zone_radius).clamp(0.0, 1.0)
+    }
+
+    pub fn value(
+        &self,
+        loot: &Loot,
+        me: &Unit,
+        team: &[Unit],
+        zone: &Zone,
+        constants: &Constants,
+    ) -> f64 {
+        if me.action.is_some() {
+            return 0.0;
+        }
+        let teammates = team.iter().filter(|u| u.id != me.id);
+        let consumables = 1.0 + self.late_game_bonus * Self::game_phase(zone, constants);
+
+        match loot.item {
+            Item::Weapon { type_index } => self.weapon_value(type_index, me),
+            Item::Ammo {
+                weapon_type_index,
+                amount,
+            } => {
+                let max_ammo = constants.weapons[weapon_type_index as usize].max_inventory_ammo;
+                let deficit = |unit: &Unit| {
+                    let missing = max_ammo - unit.ammo[weapon_type_index as usize];
+                    amount.min(missing).max(0) as f64 / max_ammo as f64
+                };
+                if deficit(me) == 0.0 {
+                    return 0.0;
+                }
+                let my_need = match me.weapon {
+                    Some(weapon)
+                        if weapon != weapon_type_index
+                            && weapon_type_index != self.preferred_weapon() =>
+                    {
+                        0.0
+                    }
+                    _ => deficit(me),
+                };
+                let team_need = teammates
+                    .filter(|u| u.weapon == Some(weapon_type_index))
+                    .map(deficit)
+                    .fold(0.0, f64::max);
+                self.ammo_weight * consumables * my_need.max(self.share_weight * team_need)
+            }
+            Item::ShieldPotions { amount } => {
+                let capacity = constants.max_shield_potions_in_inventory;
+                let free = capacity - me.shield_potions;
+                if free <= 0 {
+                    return 0.0;
+                }
+                let shield_deficit = 1.0 - me.shield / constants.max_shield;
+                let team_potions =
+                    teammates.map(|u| u.shield_potions).sum::<i32>() + me.shield_potions;
+                let team_capacity = capacity * (team.len().max(1) as i32);
+                let scarcity = 1.0 - team_potions as f64 / team_capacity as f64;
+
+                self.potion_weight
+                    * consumables
+                    * (amount.min(free) as f64 / capacity as f64)
+                    * (0.5 + 0.5 * shield_deficit)
+                    * (0.5 + 0.5 * scarcity)
+            }
+        }
+    }
+
+    /// Upgrade by tier, a weapon without ammo is worth picking up only when ours is empty too
+    fn weapon_value(&self, weapon: i32, me: &Unit) -> f64 {
+        let has_ammo = me.ammo[weapon as usize] > 0;
+        match me.weapon {
+            None => self.tier(weapon),
+            Some(my_weapon) if me.ammo[my_weapon as usize] == 0 => {
+                if has_ammo {
+                    self.tier(weapon)
+                } else {
+                    (self.tier(weapon) - self.tier(my_weapon)).max(0.0) * 0.5
+                }
+            }
+            Some(my_weapon) if has_ammo => (self.tier(weapon) - self.tier(my_weapon)).max(0.0),
+            Some(_) => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_weapon_tiers() {
+        assert_eq!(parse_weapon_tiers("1, 0.5,0"), Some(vec![1.0, 0.5, 0.0]));
+        assert_eq!(parse_weapon_tiers("1,bow"), None);
+        assert_eq!(parse_weapon_tiers(""), None);
+    }
+
+    #[test]
+    fn prefers_the_best_tier() {
+        let utility = LootUtility {
+            weapon_tiers: vec![1.0, 0.2, 0.5],
+            ..LootUtility::new(&test_constants())
+        };
+        assert_eq!(utility.preferred_weapon(), WAND);
+    }
+}
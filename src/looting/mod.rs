@@ -0,0 +1,7 @@
+mod inventory_manager;
+mod loot_utility;
+mod route_planner;
+
+pub use inventory_manager::*;
+pub use loot_utility::*;
+pub use route_planner::*;
@@ -0,0 +1,242 @@
+use crate::model::*;
+
+/// Ordered sequence of items to pick up
+#[derive(Clone, Debug)]
+pub struct LootRoute {
+    pub items: Vec<Loot>,
+    /// Summed value of all items
+    pub value: f64,
+    /// Seconds to walk the route and pick up every item
+    pub duration: f64,
+}
+
+impl LootRoute {
+    pub fn next(&self) -> Option<&Loot> {
+        self.items.first()
+    }
+
+    pub fn score(&self) -> f64 {
+        self.value / self.duration.max(f64::EPSILON)
+    }
+}
+
+/// Chooses the route with the best value per second which ends inside the forecasted zone. Items
+/// are valued for the unit with everything picked up earlier on the route.
+#[derive(Clone, Debug)]
+pub struct LootRoutePlanner {
+    /// Max items in one route
+    pub max_items: usize,
+    /// Only that many nearest valuable items are considered
+    pub candidates: usize,
+    /// Items closer than that to the zone border at arrival time are skipped, in unit radii
+    pub zone_margin: f64,
+}
+
+impl Default for LootRoutePlanner {
+    fn default() -> Self {
+        Self {
+            max_items: 4,
+            candidates: 8,
+            zone_margin: 2.0,
+        }
+    }
+}
+
+struct Search<'a, F> {
+    candidates: Vec<&'a Loot>,
+    zone: &'a Zone,
+    constants: &'a Constants,
+    margin: f64,
+    max_items: usize,
+    value: F,
+}
+
+impl<'a, F> Search<'a, F>
+where
+    F: Fn(&Loot, &Unit) -> f64,
+{
+    fn is_reachable(&self, position: Vec2, seconds: f64) -> bool {
+        position.distance_to(&self.zone.center_after(seconds, self.constants))
+            <= self.zone.radius_after(seconds, self.constants) - self.margin
+    }
+
+    fn visit(
+        &self,
+        unit: &Unit,
+        position: Vec2,
+        route: &mut Vec<usize>,
+        value: f64,
+        duration: f64,
+        best: &mut Option<(Vec<usize>, f64, f64)>,
+    ) {
+        if !route.is_empty()
+            && best
+                .as_ref()
+                .map_or(true, |(_, v, d)| value / duration > v / d)
+        {
+            *best = Some((route.clone(), value, duration));
+        }
+        if route.len() == self.max_items {
+            return;
+        }
+
+        for (i, loot) in self.candidates.iter().enumerate() {
+            if route.contains(&i) {
+                continue;
+            }
+            let loot_value = (self.value)(loot, unit);
+            if loot_value <= 0.0 {
+                continue;
+            }
+            let arrival = duration
+                + position.distance_to(&loot.position) / self.constants.max_unit_forward_speed;
+            let finish = arrival + self.constants.looting_time;
+            if !self.is_reachable(loot.position, finish) {
+                continue;
+            }
+            route.push(i);
+            let unit = unit.picked_up(loot, self.constants);
+            self.visit(
+                &unit,
+                loot.position,
+                route,
+                value + loot_value,
+                finish,
+                best,
+            );
+            route.pop();
+        }
+    }
+}
+
+impl LootRoutePlanner {
+    pub fn plan<F>(
+        &self,
+        me: &Unit,
+        loot: &[&Loot],
+        zone: &Zone,
+        constants: &Constants,
+        value: F,
+    ) -> Option<LootRoute>
+    where
+        F: Fn(&Loot, &Unit) -> f64,
+    {
+        let mut candidates = loot
+            .iter()
+            .filter(|l| value(l, me) > 0.0)
+            .cloned()
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| {
+            a.position
+                .square_distance_to(&me.position)
+                .total_cmp(&b.position.square_distance_to(&me.position))
+        });
+        candidates.truncate(self.candidates);
+
+        let search = Search {
+            candidates,
+            zone,
+            constants,
+            margin: self.zone_margin * constants.unit_radius,
+            max_items: self.max_items,
+            value,
+        };
+        let mut best = None;
+        search.visit(me, me.position, &mut Vec::new(), 0.0, 0.0, &mut best);
+
+        best.map(|(route, value, duration)| LootRoute {
+            items: route
+                .into_iter()
+                .map(|i| search.candidates[i].clone())
+                .collect(),
+            value,
+            duration,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn potions(id: i32, x: f64, y: f64) -> Loot {
+        Loot {
+            id,
+            position: Vec2::new(x, y),
+            item: Item::ShieldPotions { amount: 1 },
+        }
+    }
+
+    fn zone(radius: f64) -> Zone {
+        Zone {
+            current_radius: radius,
+            next_radius: radius,
+            ..Zone::default()
+        }
+    }
+
+    #[test]
+    fn collects_nearby_items_first() {
+        let constants = test_constants();
+        let me = test_unit(1, 1, Vec2::zero());
+        let loot = [
+            potions(1, 40.0, 0.0),
+            potions(2, 5.0, 0.0),
+            potions(3, 6.0, 0.0),
+        ];
+        let loot = loot.iter().collect::<Vec<_>>();
+        let route = LootRoutePlanner::default()
+            .plan(&me, &loot, &zone(100.0), &constants, |_, _| 1.0)
+            .unwrap();
+        assert_eq!(route.next().unwrap().id, 2);
+        assert_eq!(route.items[1].id, 3);
+    }
+
+    #[test]
+    fn skips_items_outside_of_zone() {
+        let constants = test_constants();
+        let me = test_unit(1, 1, Vec2::zero());
+        let loot = [potions(1, 5.0, 0.0), potions(2, 0.0, 25.0)];
+        let loot = loot.iter().collect::<Vec<_>>();
+        let route = LootRoutePlanner::default()
+            .plan(&me, &loot, &zone(20.0), &constants, |_, _| 1.0)
+            .unwrap();
+        assert_eq!(route.items.len(), 1);
+        assert_eq!(route.next().unwrap().id, 1);
+    }
+
+    #[test]
+    fn does_not_plan_more_than_fits_into_inventory() {
+        let constants = test_constants();
+        let me = test_unit(1, 1, Vec2::zero());
+        let loot = [
+            potions(1, 2.0, 0.0),
+            potions(2, 3.0, 0.0),
+            potions(3, 4.0, 0.0),
+        ];
+        let loot = loot.iter().collect::<Vec<_>>();
+        let fits = |_: &Loot, unit: &Unit| {
+            if unit.shield_potions < constants.max_shield_potions_in_inventory {
+                1.0
+            } else {
+                0.0
+            }
+        };
+        let route = LootRoutePlanner::default()
+            .plan(&me, &loot, &zone(100.0), &constants, fits)
+            .unwrap();
+        assert_eq!(route.items.len(), 2);
+        assert_eq!(route.value, 2.0);
+    }
+
+    #[test]
+    fn no_route_without_value() {
+        let constants = test_constants();
+        let me = test_unit(1, 1, Vec2::zero());
+        let loot = [potions(1, 5.0, 0.0)];
+        let loot = loot.iter().collect::<Vec<_>>();
+        let route =
+            LootRoutePlanner::default().plan(&me, &loot, &zone(100.0), &constants, |_, _| 0.0);
+        assert!(route.is_none());
+    }
+}
@@ -48,7 +48,7 @@ impl Runner {
             writer: Box::new(writer),
         })
     }
-    fn debug_interface(&mut self) -> DebugInterface {
+    fn debug_interface(&mut self) -> DebugInterface<'_> {
         DebugInterface::new(&mut self.reader, &mut self.writer)
     }
     fn run(mut self) -> std::io::Result<()> {
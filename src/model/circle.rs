@@ -22,7 +22,7 @@ impl Circle {
         let a = line.end - line.start;
         let b = self.center - line.start;
         let c = a.dot(&b) / a.dot(&a);
-        if c < 0.0 || c > 1.0 {
+        if !(0.0..=1.0).contains(&c) {
             return false;
         }
         let p = line.start + a * c;
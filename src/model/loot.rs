@@ -4,8 +4,6 @@ pub static WAND: i32 = 0;
 pub static STAFF: i32 = 1;
 pub static BOW: i32 = 2;
 
-static PREFERRED_WEAPON: i32 = BOW;
-
 /// Loot lying on the ground
 #[derive(Clone, Debug)]
 pub struct Loot {
@@ -19,11 +17,7 @@ pub struct Loot {
 
 impl Loot {
     pub fn is_weapon(&self) -> bool {
-        if let Item::Weapon { .. } = self.item {
-            true
-        } else {
-            false
-        }
+        matches!(self.item, Item::Weapon { .. })
     }
 
     pub fn is_ammo_for(&self, weapon_idx: i32) -> bool {
@@ -38,53 +32,7 @@ impl Loot {
     }
 
     pub fn is_first_take_loot(&self) -> bool {
-        match self.item {
-            Item::ShieldPotions { .. } => true,
-            _ => false,
-        }
-    }
-
-    pub fn is_useful_to_me(&self, me: &Unit, constants: &Constants) -> bool {
-        if me.action.is_some() {
-            return false;
-        }
-        let item = &self.item;
-        match item {
-            &Item::Weapon { type_index } => {
-                if me.weapon.is_none() {
-                    return true;
-                }
-
-                let my_weapon = me.weapon.unwrap();
-                if type_index != PREFERRED_WEAPON {
-                    return me.ammo[my_weapon as usize] == 0 && me.ammo[type_index as usize] > 0;
-                }
-
-                if my_weapon == PREFERRED_WEAPON {
-                    return false;
-                }
-
-                me.ammo[type_index as usize] > 0
-            }
-            &Item::Ammo {
-                weapon_type_index, ..
-            } => {
-                if me.ammo[weapon_type_index as usize]
-                    == constants.weapons[weapon_type_index as usize].max_inventory_ammo
-                {
-                    return false;
-                }
-
-                if let Some(my_weapon) = me.weapon {
-                    weapon_type_index == PREFERRED_WEAPON || weapon_type_index == my_weapon
-                } else {
-                    true
-                }
-            }
-            Item::ShieldPotions { .. } => {
-                me.shield_potions != constants.max_shield_potions_in_inventory
-            }
-        }
+        matches!(self.item, Item::ShieldPotions { .. })
     }
 }
 
@@ -42,3 +42,7 @@ mod vec2i;
 pub use self::vec2i::*;
 mod zone;
 pub use self::zone::*;
+#[cfg(test)]
+mod test_fixtures;
+#[cfg(test)]
+pub use self::test_fixtures::*;
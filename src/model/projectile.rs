@@ -32,6 +32,23 @@ impl Projectile {
         Line::new(self.position, self.position + self.moving_vec())
     }
 
+    /// Where the projectile will be in given seconds, life time can become negative
+    pub fn advanced(&self, seconds: f64) -> Self {
+        Self {
+            position: self.position + self.velocity * seconds,
+            life_time: self.life_time - seconds,
+            ..self.clone()
+        }
+    }
+
+    /// Whether the projectile crosses the circle during given seconds
+    pub fn hits_circle_within(&self, circle: &Circle, seconds: f64) -> bool {
+        let path = Line::new(self.position, self.position + self.velocity * seconds);
+        circle.contains(&path.start)
+            || circle.contains(&path.end)
+            || circle.intercept_with_line(&path)
+    }
+
     pub fn is_dangerous(&self, me: &Unit, constants: &Constants) -> bool {
         if self.shooter_player_id == me.player_id && !constants.friendly_fire {
             // Это моя пуля и френдли фаер выключен
@@ -0,0 +1,98 @@
+use super::*;
+
+fn weapon(
+    name: &str,
+    rounds_per_second: f64,
+    spread: f64,
+    aim_time: f64,
+    projectile_speed: f64,
+    projectile_damage: f64,
+    projectile_life_time: f64,
+) -> WeaponProperties {
+    WeaponProperties {
+        name: name.to_string(),
+        rounds_per_second,
+        spread,
+        aim_time,
+        aim_field_of_view: 30.0,
+        aim_rotation_speed: 30.0,
+        aim_movement_speed_modifier: 0.5,
+        projectile_speed,
+        projectile_damage,
+        projectile_life_time,
+        shot_sound_type_index: None,
+        projectile_hit_sound_type_index: None,
+        max_inventory_ammo: 100,
+    }
+}
+
+/// Constants close to the ones of the final rounds, without obstacles
+pub fn test_constants() -> Constants {
+    Constants {
+        ticks_per_second: 30.0,
+        team_size: 1,
+        initial_zone_radius: 300.0,
+        zone_speed: 1.0,
+        zone_damage_per_second: 5.0,
+        spawn_time: 5.0,
+        spawn_collision_damage_per_second: 100.0,
+        looting_time: 0.3,
+        bot_players: 0,
+        unit_radius: 1.0,
+        unit_health: 100.0,
+        health_regeneration_per_second: 10.0,
+        health_regeneration_delay: 5.0,
+        max_shield: 100.0,
+        spawn_shield: 100.0,
+        extra_lives: 2,
+        last_respawn_zone_radius: 150.0,
+        field_of_view: 90.0,
+        view_distance: 60.0,
+        view_blocking: true,
+        rotation_speed: 90.0,
+        spawn_movement_speed: 5.0,
+        max_unit_forward_speed: 10.0,
+        max_unit_backward_speed: 5.0,
+        unit_acceleration: 30.0,
+        friendly_fire: false,
+        kill_score: 1000.0,
+        damage_score_multiplier: 1.0,
+        score_per_place: 100.0,
+        weapons: vec![
+            weapon("Wand", 2.0, 3.0, 0.3, 40.0, 20.0, 0.8),
+            weapon("Staff", 1.0, 15.0, 0.1, 30.0, 50.0, 0.6),
+            weapon("Bow", 1.0, 1.0, 1.0, 60.0, 70.0, 0.6),
+        ],
+        starting_weapon: Some(WAND),
+        starting_weapon_ammo: 20,
+        max_shield_potions_in_inventory: 2,
+        shield_per_potion: 50.0,
+        shield_potion_use_time: 1.0,
+        sounds: Vec::new(),
+        steps_sound_type_index: None,
+        steps_sound_travel_distance: 10.0,
+        obstacles: Vec::new(),
+    }
+}
+
+/// Standing unit at full health looking along the x axis
+pub fn test_unit(id: i32, player_id: i32, position: Vec2) -> Unit {
+    Unit {
+        id,
+        player_id,
+        health: 100.0,
+        shield: 0.0,
+        extra_lives: 2,
+        position,
+        remaining_spawn_time: None,
+        velocity: Vec2::zero(),
+        direction: Vec2::new(1.0, 0.0),
+        aim: 0.0,
+        action: None,
+        health_regeneration_start_tick: 0,
+        weapon: Some(BOW),
+        next_shot_tick: 0,
+        ammo: vec![0, 0, 10],
+        shield_potions: 0,
+    }
+}
@@ -39,6 +39,11 @@ pub struct Unit {
 }
 
 impl Unit {
+    /// Whether the unit comes back if killed now, respawning stops when the zone gets small
+    pub fn can_respawn(&self, zone: &Zone, constants: &Constants) -> bool {
+        self.extra_lives > 0 && zone.current_radius > constants.last_respawn_zone_radius
+    }
+
     pub fn as_circle(&self, unit_radius: f64) -> Circle {
         Circle::new(self.position, unit_radius)
     }
@@ -64,6 +69,64 @@ impl Unit {
         .to_radians()
     }
 
+    /// Velocity limit is a circle shifted towards the view direction
+    pub fn clamp_velocity(&self, velocity: Vec2, constants: &Constants) -> Vec2 {
+        let aim_modifier = if let Some(weapon) = self.weapon {
+            1.0 - (1.0 - constants.weapons[weapon as usize].aim_movement_speed_modifier) * self.aim
+        } else {
+            1.0
+        };
+        let forward = constants.max_unit_forward_speed * aim_modifier;
+        let backward = constants.max_unit_backward_speed * aim_modifier;
+        let center = self.direction.normalize() * ((forward - backward) / 2.0);
+        let radius = (forward + backward) / 2.0;
+
+        let shifted = velocity - center;
+        if shifted.length() <= radius {
+            return velocity;
+        }
+        // intersection of the ray from zero with the limit circle
+        let dir = velocity.normalize();
+        let b = dir.dot(&center);
+        let c = center.dot(&center) - radius * radius;
+        let t = b + (b * b - c).max(0.0).sqrt();
+
+        dir * t
+    }
+
+    /// Velocity after one tick of accelerating towards the target velocity
+    pub fn next_velocity(&self, velocity: Vec2, target: Vec2, constants: &Constants) -> Vec2 {
+        let target = self.clamp_velocity(target, constants);
+        let max_delta = constants.unit_acceleration / constants.ticks_per_second;
+        let delta = target - velocity;
+        if delta.length() <= max_delta {
+            target
+        } else {
+            velocity + delta.normalize() * max_delta
+        }
+    }
+
+    /// Unit's inventory after picking up the item, a weapon replaces the current one
+    pub fn picked_up(&self, loot: &Loot, constants: &Constants) -> Unit {
+        let mut unit = self.clone();
+        match loot.item {
+            Item::Weapon { type_index } => unit.weapon = Some(type_index),
+            Item::Ammo {
+                weapon_type_index,
+                amount,
+            } => {
+                let max_ammo = constants.weapons[weapon_type_index as usize].max_inventory_ammo;
+                let ammo = &mut unit.ammo[weapon_type_index as usize];
+                *ammo = (*ammo + amount).min(max_ammo);
+            }
+            Item::ShieldPotions { amount } => {
+                unit.shield_potions =
+                    (unit.shield_potions + amount).min(constants.max_shield_potions_in_inventory);
+            }
+        }
+        unit
+    }
+
     pub fn is_in_fov(&self, position: Vec2, constants: &Constants) -> bool {
         let vec = position - self.position;
         if vec.length() >= constants.view_distance {
@@ -132,3 +195,53 @@ impl trans::Trans for Unit {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        (a - b).length() < 1e-9
+    }
+
+    #[test]
+    fn clamp_velocity_keeps_reachable_velocity() {
+        let constants = test_constants();
+        let mut unit = test_unit(1, 1, Vec2::zero());
+        unit.weapon = None;
+        let velocity = Vec2::new(0.0, 5.0);
+        assert!(close(unit.clamp_velocity(velocity, &constants), velocity));
+    }
+
+    #[test]
+    fn clamp_velocity_limits_forward_and_backward() {
+        let constants = test_constants();
+        let mut unit = test_unit(1, 1, Vec2::zero());
+        unit.weapon = None;
+        let forward = unit.clamp_velocity(Vec2::new(100.0, 0.0), &constants);
+        assert!(close(forward, Vec2::new(10.0, 0.0)));
+        let backward = unit.clamp_velocity(Vec2::new(-100.0, 0.0), &constants);
+        assert!(close(backward, Vec2::new(-5.0, 0.0)));
+    }
+
+    #[test]
+    fn clamp_velocity_slows_down_while_aiming() {
+        let constants = test_constants();
+        let mut unit = test_unit(1, 1, Vec2::zero());
+        unit.aim = 1.0;
+        let forward = unit.clamp_velocity(Vec2::new(100.0, 0.0), &constants);
+        assert!(close(forward, Vec2::new(5.0, 0.0)));
+    }
+
+    #[test]
+    fn next_velocity_is_limited_by_acceleration() {
+        let constants = test_constants();
+        let unit = test_unit(1, 1, Vec2::zero());
+        let step = constants.unit_acceleration / constants.ticks_per_second;
+        let next = unit.next_velocity(Vec2::zero(), Vec2::new(10.0, 0.0), &constants);
+        assert!(close(next, Vec2::new(step, 0.0)));
+        let near = Vec2::new(10.0 - step / 2.0, 0.0);
+        let reached = unit.next_velocity(near, Vec2::new(10.0, 0.0), &constants);
+        assert!(close(reached, Vec2::new(10.0, 0.0)));
+    }
+}
@@ -13,6 +13,24 @@ pub struct Zone {
     pub next_radius: f64,
 }
 
+impl Zone {
+    /// Current radius after given seconds of shrinking
+    pub fn radius_after(&self, seconds: f64, constants: &Constants) -> f64 {
+        (self.current_radius - constants.zone_speed * seconds).max(0.0)
+    }
+
+    /// Current center after given seconds, it moves to the next center while the radius shrinks
+    /// to the next radius
+    pub fn center_after(&self, seconds: f64, constants: &Constants) -> Vec2 {
+        let shrink = self.current_radius - self.next_radius;
+        if shrink <= 0.0 {
+            return self.next_center;
+        }
+        let progress = (constants.zone_speed * seconds / shrink).min(1.0);
+        self.current_center + (self.next_center - self.current_center) * progress
+    }
+}
+
 impl Default for Zone {
     fn default() -> Self {
         Self {
@@ -45,3 +63,32 @@ impl trans::Trans for Zone {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn radius_after_shrinks_and_stops_at_zero() {
+        let constants = test_constants();
+        let zone = Zone {
+            current_radius: 10.0,
+            ..Zone::default()
+        };
+        assert_eq!(zone.radius_after(4.0, &constants), 6.0);
+        assert_eq!(zone.radius_after(20.0, &constants), 0.0);
+    }
+
+    #[test]
+    fn center_after_reaches_next_center_with_next_radius() {
+        let constants = test_constants();
+        let zone = Zone {
+            current_center: Vec2::zero(),
+            current_radius: 10.0,
+            next_center: Vec2::new(4.0, 0.0),
+            next_radius: 6.0,
+        };
+        assert_eq!(zone.center_after(2.0, &constants), Vec2::new(2.0, 0.0));
+        assert_eq!(zone.center_after(10.0, &constants), Vec2::new(4.0, 0.0));
+    }
+}
@@ -1,43 +1,125 @@
 use crate::debug_interface::DebugInterface;
+use ai_cup_22::behaviour::*;
+use ai_cup_22::combat::*;
+use ai_cup_22::debugging::Color;
+use ai_cup_22::looting::*;
 use ai_cup_22::model::*;
 use ai_cup_22::potential_field::*;
+use std::collections::HashMap;
 use std::f64::consts::PI;
 
+/// Half size of the field grid rasterised around a unit the planner found no way for, in unit radii
+const GRID_HALF_SIZE: f64 = 3.0;
+/// Distance between cells of the field grid, in unit radii
+const GRID_STEP: f64 = 0.5;
+/// Remembered enemies less certain than that are neither targeted nor feared
+const MIN_ENEMY_CONFIDENCE: f64 = 0.3;
+
 pub struct MyStrategy {
     constants: Constants,
     pp: PotentialField,
-    obstacles_cant_shoot_through: Vec<Obstacle>,
+    dumper: Option<FieldDumper>,
+    planner: TrajectoryPlanner,
+    target_scorer: TargetScorer,
+    aim_controller: AimController,
+    potion_policy: PotionPolicy,
+    inventory: InventoryManager,
+    route_planner: LootRoutePlanner,
+    spawn_planner: SpawnPlanner,
+    behaviour: BehaviourMachine,
+    dodge_planner: DodgePlanner,
+    look_controller: LookController,
+    team_coordinator: TeamCoordinator,
+    posture: StrategicPosture,
+    life_risk: LifeRisk,
 }
 
 impl MyStrategy {
     pub fn new(constants: Constants) -> Self {
         // dbg!(&constants);
         let pp = PotentialField::new(&constants);
+        let dumper = DumpConfig::from_env().and_then(|config| match FieldDumper::new(config) {
+            Ok(dumper) => Some(dumper),
+            Err(err) => {
+                eprintln!("Field dump is disabled: {}", err);
+                None
+            }
+        });
         Self {
-            obstacles_cant_shoot_through: constants
-                .obstacles
-                .iter()
-                .filter(|o| !o.can_shoot_through)
-                .cloned()
-                .collect(),
             constants,
             pp,
+            dumper,
+            planner: TrajectoryPlanner::default(),
+            target_scorer: TargetScorer::default(),
+            aim_controller: AimController::default(),
+            potion_policy: PotionPolicy::default(),
+            inventory: InventoryManager::default(),
+            route_planner: LootRoutePlanner::default(),
+            spawn_planner: SpawnPlanner::default(),
+            behaviour: BehaviourMachine::default(),
+            dodge_planner: DodgePlanner::default(),
+            look_controller: LookController::default(),
+            team_coordinator: TeamCoordinator::default(),
+            posture: StrategicPosture::default(),
+            life_risk: LifeRisk::default(),
         }
     }
     pub fn get_order(
         &mut self,
         game: &Game,
-        _debug_interface: Option<&mut DebugInterface>,
+        mut debug_interface: Option<&mut DebugInterface>,
     ) -> Order {
-        // let debug_interface = _debug_interface.unwrap();
         self.pp.update(game);
+        self.inventory.update(game, &self.constants);
+        let dumper = self
+            .dumper
+            .as_ref()
+            .filter(|d| d.is_dump_tick(game.current_tick));
+        if let Some(dumper) = dumper {
+            if let Err(err) = dumper.dump_game(game) {
+                eprintln!("Failed to dump tick {}: {}", game.current_tick, err);
+            }
+        }
 
-        let mut enemies: Vec<&Unit> = game
+        // cloned so the field can be updated while the enemies are borrowed
+        let tracked: Vec<TrackedEnemy> = self
+            .pp
+            .enemies
+            .iter()
+            .filter(|e| e.confidence >= MIN_ENEMY_CONFIDENCE)
+            .cloned()
+            .collect();
+        let enemies: Vec<&TrackedEnemy> = tracked.iter().collect();
+        let allies: Vec<&Unit> = game
             .units
             .iter()
-            .filter(|u| u.player_id != game.my_id)
+            .filter(|u| u.player_id == game.my_id)
+            .collect();
+        self.life_risk.update(game);
+        self.behaviour.aggression =
+            self.posture
+                .aggression(game, &self.pp, &enemies, &self.life_risk, &self.constants);
+        let assignments =
+            self.team_coordinator
+                .assign(&self.target_scorer, &allies, &enemies, &self.constants);
+        self.pp.targets = assignments
+            .iter()
+            .map(|(&unit_id, target)| (unit_id, target.enemy.id))
             .collect();
-        enemies.extend(self.pp.old_enemies.iter());
+
+        let mut behaviours = HashMap::new();
+        for me in allies.iter() {
+            let aggression_shift =
+                self.life_risk
+                    .aggression_shift(me, &game.zone, game.current_tick, &self.constants);
+            let behaviour =
+                self.behaviour
+                    .update(&self.pp, me, &enemies, aggression_shift, game.current_tick);
+            behaviours.insert(me.id, behaviour);
+        }
+
+        let visible_loot: Vec<&Loot> = game.loot.iter().collect();
+        let available_loot: Vec<&Loot> = self.pp.loot.available().collect();
 
         // for sound in game.sounds.iter() {
         //     debug_interface.add_circle(sound.position, 1.0, Color::new(0.0, 1.0, 0.0, 0.8));
@@ -52,7 +134,7 @@ impl MyStrategy {
         //     );
         // }
 
-        Order {
+        let order = Order {
             unit_orders: game
                 .units
                 .iter()
@@ -86,73 +168,44 @@ impl MyStrategy {
                     //     0.5,
                     //     Color::new(0.0, 0.0, 0.0, 0.8),
                     // );
-                    // for enemy in self.pp.old_enemies.iter() {
-                    //     if me.is_in_fov(enemy.position, &self.constants) {
+                    // for enemy in self.pp.enemies.remembered() {
+                    //     if me.is_in_fov(enemy.unit.position, &self.constants) {
                     //         debug_interface.add_circle(
-                    //             enemy.position,
+                    //             enemy.unit.position,
                     //             1.0,
                     //             Color::new(1.0, 0.0, 0.0, 0.5),
                     //         );
                     //     } else {
                     //         debug_interface.add_circle(
-                    //             enemy.position,
+                    //             enemy.unit.position,
                     //             1.0,
                     //             Color::new(0.0, 1.0, 0.0, 0.5),
                     //         );
                     //     }
                     // }
-                    let i_have_weapon =
-                        me.weapon.is_some() && me.ammo[me.weapon.unwrap() as usize] > 0;
-
-                    let enemy_has_good_weapon = enemies.iter().any(|enemy| {
-                        if let Some(weapon) = enemy.weapon {
-                            (weapon == BOW || weapon == STAFF) && enemy.ammo[weapon as usize] > 0
-                        } else {
-                            false
-                        }
-                    });
-                    let me_has_good_weapon = if let Some(weapon) = me.weapon {
-                        (weapon == BOW || weapon == STAFF) && me.ammo[weapon as usize] > 0
-                    } else {
-                        false
-                    };
+                    let behaviour = behaviours[&me.id];
+                    let policy = behaviour.policy();
 
-                    let fight_mode = if i_have_weapon {
-                        if me_has_good_weapon || !enemy_has_good_weapon {
-                            FightMode::Attack
-                        } else {
-                            FightMode::Defend
+                    if let Some(dumper) = dumper {
+                        if let Err(err) =
+                            dumper.dump_unit(&self.pp, game.current_tick, me, behaviour)
+                        {
+                            eprintln!("Failed to dump unit {}: {}", me.id, err);
                         }
-                    } else {
-                        FightMode::RunWithNoWeapons
-                    };
+                    }
 
-                    // match fight_mode {
-                    //     FightMode::Attack => debug_interface.add_placed_text(
-                    //         me.position + Vec2::new(0.0, 1.0),
-                    //         "Attack".to_string(),
-                    //         Vec2::zero(),
-                    //         0.5,
-                    //         Color::BLACK,
-                    //     ),
-                    //     FightMode::Defend => debug_interface.add_placed_text(
-                    //         me.position + Vec2::new(0.0, 1.0),
-                    //         "Defend".to_string(),
-                    //         Vec2::zero(),
-                    //         0.5,
-                    //         Color::BLACK,
-                    //     ),
-                    //     FightMode::RunWithNoWeapons => debug_interface.add_placed_text(
-                    //         me.position + Vec2::new(0.0, 1.0),
-                    //         "Run".to_string(),
-                    //         Vec2::zero(),
-                    //         0.5,
-                    //         Color::BLACK,
-                    //     ),
-                    // }
+                    if let Some(debug_interface) = debug_interface.as_deref_mut() {
+                        debug_interface.add_placed_text(
+                            me.position + Vec2::new(0.0, 1.0),
+                            behaviour.to_string(),
+                            Vec2::new(0.5, 0.0),
+                            0.5,
+                            Color::BLACK,
+                        );
+                    }
 
                     // for point in self.pp.points_around(me.id).iter() {
-                    //     let value = self.pp.value(*point, me, fight_mode);
+                    //     let value = self.pp.value(*point, me, behaviour);
                     //     // debug_interface.add_circle(*point, 0.5, color_by_value(value));
                     //     debug_interface.add_placed_text(
                     //         *point,
@@ -171,331 +224,389 @@ impl MyStrategy {
                         })
                         .unwrap_or(0.0);
 
-                    let closest_enemy = if i_have_weapon {
-                        let can_shoot_right_now = enemies
-                            .iter()
-                            .filter(|u| {
-                                let distance = u.position.distance_to(&me.position)
-                                    - self.constants.unit_radius;
-                                let seconds_to_enemy = distance
-                                    / self.constants.weapons[me.weapon.unwrap() as usize]
-                                        .projectile_speed;
-
-                                let line = Line::new(me.position, u.position);
-                                u.remaining_spawn_time.unwrap_or(0.0) < seconds_to_enemy
-                                    && !self.obstacles_cant_shoot_through.iter().any(|o| {
-                                        let circle = o.as_circle(-self.constants.unit_radius);
-                                        circle.intercept_with_line(&line)
-                                    })
-                                    && !game
-                                        .units
-                                        .iter()
-                                        .filter(|u| u.player_id == game.my_id && u.id != me.id)
-                                        .any(|u| {
-                                            let circle = u.as_circle(self.constants.unit_radius);
-                                            circle.intercept_with_line(&line)
-                                        })
-                            })
-                            .min_by(|a, b| {
-                                let a_value = a.position.square_distance_to(&me.position);
-                                let b_value = b.position.square_distance_to(&me.position);
-                                a_value.partial_cmp(&b_value).unwrap()
-                            });
-                        let can_shoot_probably = enemies
-                            .iter()
-                            .filter(|u| {
-                                let distance = u.position.distance_to(&me.position)
-                                    - self.constants.unit_radius;
-                                let seconds_to_enemy = distance
-                                    / self.constants.weapons[me.weapon.unwrap() as usize]
-                                        .projectile_speed;
-
-                                let line = Line::new(me.position, u.position);
-                                u.remaining_spawn_time.unwrap_or(0.0) < seconds_to_enemy
-                                    && self
-                                        .obstacles_cant_shoot_through
-                                        .iter()
-                                        .filter(|o| {
-                                            let circle = o.as_circle(-self.constants.unit_radius);
-                                            circle.intercept_with_line(&line)
-                                        })
-                                        .count()
-                                        < 2
-                                    && !game
-                                        .units
-                                        .iter()
-                                        .filter(|u| u.player_id == game.my_id && u.id != me.id)
-                                        .any(|u| {
-                                            let circle = u.as_circle(self.constants.unit_radius);
-                                            circle.intercept_with_line(&line)
-                                        })
-                            })
-                            .min_by(|a, b| {
-                                let a_value = a.position.square_distance_to(&me.position);
-                                let b_value = b.position.square_distance_to(&me.position);
-                                a_value.partial_cmp(&b_value).unwrap()
-                            });
+                    let scored_target = assignments.get(&me.id);
+                    let target = scored_target.map(|t| t.enemy);
+                    let intercept = scored_target.map(|t| &t.intercept);
+                    if let (Some(target), Some(debug_interface)) =
+                        (target, debug_interface.as_deref_mut())
+                    {
+                        debug_interface.add_segment(
+                            me.position,
+                            target.position,
+                            0.05,
+                            Color::new(1.0, 0.0, 0.0, 0.3),
+                        );
+                    }
 
-                        if let Some(enemy) = can_shoot_right_now {
-                            Some(enemy)
-                        } else if let Some(enemy) = can_shoot_probably {
-                            Some(enemy)
-                        } else {
-                            enemies.iter().min_by(|a, b| {
-                                let a_value = a.position.square_distance_to(&me.position);
-                                let b_value = b.position.square_distance_to(&me.position);
-                                a_value.partial_cmp(&b_value).unwrap()
-                            })
-                        }
-                    } else {
-                        None
+                    let loot_value = |l: &Loot| {
+                        self.pp.loot_utility.value(
+                            l,
+                            me,
+                            self.pp.team(),
+                            &game.zone,
+                            &self.constants,
+                        )
                     };
-
-                    // TODO: get targets from other units
                     let is_in_danger = self.pp.is_in_danger(me)
-                        || (closest_enemy.is_some()
-                            && closest_enemy.unwrap().position.distance_to(&me.position)
+                        || (target.is_some()
+                            && target.unwrap().position.distance_to(&me.position)
                                 < my_weapon_range);
 
-                    let target_velocity = if me.remaining_spawn_time.is_some() {
-                        (*self
-                            .pp
-                            .points_around(me.id)
-                            .iter()
-                            .max_by(|&a, &b| {
-                                let a_value = self.pp.value_unspawned(*a, me);
-                                let b_value = self.pp.value_unspawned(*b, me);
-                                a_value.partial_cmp(&b_value).unwrap()
-                            })
-                            .unwrap_or(&game.zone.current_center)
-                            - me.position)
-                            .normalize()
-                            * self.constants.spawn_movement_speed
-                    } else if is_in_danger {
-                        let best_pp =
-                            self.pp
+                    let potion = if policy.drink {
+                        self.potion_policy.decide(me, &self.pp, game.current_tick)
+                    } else {
+                        PotionDecision::Skip
+                    };
+                    // drinking units stand still, so they look for cover like healing ones
+                    let movement_behaviour = if potion == PotionDecision::Drink {
+                        Behaviour::Heal
+                    } else {
+                        behaviour
+                    };
+                    let drinking = potion == PotionDecision::Drink && me.action.is_some();
+
+                    let landing = self.spawn_planner.plan(&self.pp, me);
+                    let target_velocity = if let Some(landing) = landing {
+                        if let Some(debug_interface) = debug_interface.as_deref_mut() {
+                            debug_interface.add_segment(
+                                me.position,
+                                landing,
+                                0.1,
+                                Color::new(0.5, 0.0, 0.5, 0.5),
+                            );
+                        }
+                        self.spawn_planner.velocity_to(me, landing, &self.constants)
+                    } else if is_in_danger
+                        || movement_behaviour.group() == BehaviourGroup::Survive
+                        || movement_behaviour == Behaviour::Kite
+                    {
+                        let trajectory = self.planner.plan(
+                            &self.pp,
+                            me,
+                            movement_behaviour,
+                            self.constants.max_unit_forward_speed,
+                            None,
+                        );
+                        if let Some(trajectory) = trajectory {
+                            if let Some(debug_interface) = debug_interface.as_deref_mut() {
+                                let mut vertices = vec![me.position];
+                                vertices.extend(trajectory.positions.iter().cloned());
+                                debug_interface.add_poly_line(
+                                    vertices,
+                                    0.1,
+                                    Color::new(0.0, 0.0, 1.0, 0.5),
+                                );
+                                let point = trajectory.positions[0];
+                                draw_value_breakdown(
+                                    debug_interface,
+                                    point,
+                                    &self.pp.value_breakdown(point, me, movement_behaviour),
+                                );
+                            }
+                            trajectory.first_velocity().unwrap()
+                        } else {
+                            // the grid is only needed when the planner found nothing
+                            let area = GridArea::Window {
+                                center: me.position,
+                                half_size: self.constants.unit_radius * GRID_HALF_SIZE,
+                            };
+                            let grid = self.pp.rasterize(
+                                me,
+                                movement_behaviour,
+                                area,
+                                self.constants.unit_radius * GRID_STEP,
+                            );
+                            let value = |position| {
+                                grid.sample(position).unwrap_or_else(|| {
+                                    self.pp.value(position, me, movement_behaviour)
+                                })
+                            };
+                            let best_pp = self
+                                .pp
                                 .points_around(me.id)
                                 .iter()
                                 .cloned()
-                                .max_by(|&a, &b| {
-                                    let a_value = self.pp.value(a, me, fight_mode);
-                                    let b_value = self.pp.value(b, me, fight_mode);
-                                    a_value.partial_cmp(&b_value).unwrap()
-                                });
-                        (best_pp.unwrap_or(game.zone.current_center) - me.position).normalize()
-                            * self.constants.max_unit_forward_speed
+                                .max_by(|&a, &b| value(a).total_cmp(&value(b)));
+                            (best_pp.unwrap_or(game.zone.current_center) - me.position).normalize()
+                                * self.constants.max_unit_forward_speed
+                        }
                     } else {
-                        let bonus = self
-                            .pp
-                            .loot
-                            .iter()
-                            .filter(|l| {
-                                l.is_useful_to_me(me, &self.constants)
-                                    && l.position.distance_to(&game.zone.current_center)
-                                        < game.zone.current_radius
-                                            - self.constants.unit_radius * 2.0
-                            })
-                            .min_by(|l, r| {
-                                l.position
-                                    .square_distance_to(&me.position)
-                                    .partial_cmp(&r.position.square_distance_to(&me.position))
-                                    .unwrap()
-                            });
+                        let route = self.route_planner.plan(
+                            me,
+                            &available_loot,
+                            &game.zone,
+                            &self.constants,
+                            |l, unit| {
+                                if self.inventory.is_reserved(l, me) {
+                                    0.0
+                                } else {
+                                    self.pp.loot_utility.value(
+                                        l,
+                                        unit,
+                                        self.pp.team(),
+                                        &game.zone,
+                                        &self.constants,
+                                    )
+                                }
+                            },
+                        );
+                        if let (Some(route), Some(debug_interface)) =
+                            (&route, debug_interface.as_deref_mut())
+                        {
+                            let mut vertices = vec![me.position];
+                            vertices.extend(route.items.iter().map(|l| l.position));
+                            debug_interface.add_poly_line(
+                                vertices,
+                                0.1,
+                                Color::new(0.0, 0.8, 0.0, 0.5),
+                            );
+                        }
 
-                        let target_position = if let Some(bonus) = bonus {
-                            bonus.position
-                        } else {
-                            let nearest_ally = game
-                                .units
-                                .iter()
-                                .filter(|u| u.player_id == game.my_id && u.id != me.id)
-                                .filter(|u| {
-                                    u.position.distance_to(&me.position)
-                                        > self.constants.unit_radius * 8.0
-                                })
-                                .min_by(|a, b| {
-                                    a.position
-                                        .square_distance_to(&me.position)
-                                        .partial_cmp(&b.position.square_distance_to(&me.position))
-                                        .unwrap()
-                                });
-                            if let Some(ally) = nearest_ally {
-                                ally.position
+                        let target_position =
+                            if let Some(bonus) = route.as_ref().and_then(|r| r.next()) {
+                                bonus.position
                             } else {
-                                let vec = if me.position.distance_to(&game.zone.current_center)
-                                    < 0.5 * game.zone.current_radius
-                                {
-                                    (me.position - game.zone.current_center) * 1.5
+                                let nearest_ally = game
+                                    .units
+                                    .iter()
+                                    .filter(|u| u.player_id == game.my_id && u.id != me.id)
+                                    .filter(|u| {
+                                        u.position.distance_to(&me.position)
+                                            > self.constants.unit_radius * 8.0
+                                    })
+                                    .min_by(|a, b| {
+                                        a.position
+                                            .square_distance_to(&me.position)
+                                            .total_cmp(&b.position.square_distance_to(&me.position))
+                                    });
+                                if let Some(ally) = nearest_ally {
+                                    ally.position
                                 } else {
-                                    (me.position - game.zone.current_center).rotate(PI / 10.0)
-                                };
-                                game.zone.current_center + vec
-                            }
-                        };
+                                    let vec = if me.position.distance_to(&game.zone.current_center)
+                                        < 0.5 * game.zone.current_radius
+                                    {
+                                        (me.position - game.zone.current_center) * 1.5
+                                    } else {
+                                        (me.position - game.zone.current_center).rotate(PI / 10.0)
+                                    };
+                                    game.zone.current_center + vec
+                                }
+                            };
 
-                        (target_position - me.position).normalize()
-                            * self.constants.max_unit_forward_speed
+                        // walking to the goal still avoids danger and keeps the field in mind
+                        self.planner
+                            .plan(
+                                &self.pp,
+                                me,
+                                movement_behaviour,
+                                self.constants.max_unit_forward_speed,
+                                Some(target_position),
+                            )
+                            .and_then(|trajectory| trajectory.first_velocity())
+                            .unwrap_or_else(|| {
+                                (target_position - me.position).normalize()
+                                    * self.constants.max_unit_forward_speed
+                            })
                     };
 
-                    let target_direction = if closest_enemy.is_some()
-                        && closest_enemy
-                            .unwrap()
-                            .position
-                            .square_distance_to(&me.position)
-                            <= my_weapon_range.powi(2) * 1.5
-                    {
-                        let distance = closest_enemy.unwrap().position.distance_to(&me.position)
-                            - self.constants.unit_radius;
-                        let seconds_to_enemy = distance
-                            / self.constants.weapons[me.weapon.unwrap() as usize].projectile_speed;
-                        closest_enemy.unwrap().position
-                            + closest_enemy.unwrap().velocity * seconds_to_enemy * 0.77
-                            - me.position
-                    } else if let Some(sound) = self.pp.sounds().iter().min_by(|a, b| {
-                        let a_dist = me.position.square_distance_to(&a.position);
-                        let b_dist = me.position.square_distance_to(&b.position);
-                        a_dist.partial_cmp(&b_dist).unwrap()
+                    // dodging projectiles overrides any other movement
+                    let target_velocity = self
+                        .dodge_planner
+                        .plan(&self.pp, me, target_velocity)
+                        .unwrap_or(target_velocity);
+
+                    let aim_point = intercept.map(|intercept| intercept.point);
+
+                    let target_direction = if let Some(target) = target.filter(|e| {
+                        e.position.square_distance_to(&me.position) <= my_weapon_range.powi(2) * 1.5
                     }) {
-                        sound.position - me.position
+                        aim_point.unwrap_or(target.position) - me.position
                     } else {
-                        target_velocity
+                        self.look_controller
+                            .direction(&self.pp, me, target_velocity, behaviour)
                     }
                     .normalize();
 
-                    (
-                        me.id,
-                        UnitOrder {
-                            target_velocity,
-                            target_direction,
-                            action: if closest_enemy.is_some()
-                                && closest_enemy
-                                    .unwrap()
-                                    .position
-                                    .square_distance_to(&me.position)
-                                    < my_weapon_range.powi(2) * 1.5
-                            {
-                                let closest_enemy = closest_enemy.unwrap();
-                                let weapon_id = me.weapon.unwrap() as usize;
-                                let weapon = &self.constants.weapons[weapon_id];
-                                let weapon_range = weapon.projectile_life_time
-                                    * weapon.projectile_speed
-                                    + self.constants.unit_radius * 2.0;
-                                let mut aim = Line::new(
-                                    me.position,
-                                    me.position + (me.direction.normalize() * weapon_range),
-                                );
-                                let d = closest_enemy.position.distance_to(&me.position);
-                                if aim.length() > d + self.constants.unit_radius * 2.0 {
-                                    aim.set_length(d + self.constants.unit_radius * 2.0)
-                                }
+                    let inventory_action = if is_in_danger || !policy.share {
+                        None
+                    } else {
+                        self.inventory.decide(
+                            me,
+                            &allies,
+                            &visible_loot,
+                            self.pp.loot_utility.preferred_weapon(),
+                            &self.constants,
+                        )
+                    };
+                    let idle_action = || {
+                        let pickup = self.pp.loot.available().find(|l| {
+                            policy.pickup
+                                && l.position.distance_to(&me.position)
+                                    <= self.constants.unit_radius
+                                && loot_value(l) > 0.0
+                                && !self.inventory.is_reserved(l, me)
+                        });
+                        if behaviour == Behaviour::Heal && potion == PotionDecision::Drink {
+                            Some(ActionOrder::UseShieldPotion {})
+                        } else if inventory_action.is_some() {
+                            // dropping the empty weapon goes before picking up the new one
+                            inventory_action.clone()
+                        } else if let Some(loot) = pickup {
+                            Some(ActionOrder::Pickup { loot: loot.id })
+                        } else if potion == PotionDecision::Drink {
+                            Some(ActionOrder::UseShieldPotion {})
+                        } else {
+                            None
+                        }
+                    };
+
+                    let aim_decision = if let (Some(target), Some(intercept)) = (
+                        target.filter(|e| {
+                            e.position.square_distance_to(&me.position)
+                                < my_weapon_range.powi(2) * 1.5
+                        }),
+                        intercept,
+                    ) {
+                        let weapon_id = me.weapon.unwrap() as usize;
+                        let weapon = &self.constants.weapons[weapon_id];
+                        let weapon_range = weapon.projectile_life_time * weapon.projectile_speed
+                            + self.constants.unit_radius * 2.0;
+                        let mut aim = Line::new(
+                            me.position,
+                            me.position + (me.direction.normalize() * weapon_range),
+                        );
+                        let d = intercept.point.distance_to(&me.position);
+                        if aim.length() > d + self.constants.unit_radius * 2.0 {
+                            aim.set_length(d + self.constants.unit_radius * 2.0)
+                        }
 
-                                let seconds_to_enemy =
+                        // debug_interface.add_poly_line(
+                        //     vec![aim.start, aim.end],
+                        //     0.1,
+                        //     Color::new(0.0, 0.0, 1.0, 0.5),
+                        // );
+                        // for o in self.constants.obstacles.iter().cloned() {
+                        //     let circle = Circle::new(o.position, o.radius);
+                        //     if circle.intercept_with_line(&aim) {
+                        //         debug_interface.add_circle(
+                        //             o.position,
+                        //             o.radius,
+                        //             Color::new(1.0, 0.0, 0.0, 0.5),
+                        //         );
+                        //     }
+                        // }
+                        let obstacles_on_line = self
+                            .constants
+                            .obstacles
+                            .iter()
+                            .filter(|o| !o.can_shoot_through)
+                            .filter(|o| o.as_circle(0.0).intercept_with_line(&aim))
+                            .count();
+                        let unit_on_line = game
+                            .units
+                            .iter()
+                            .filter(|u| u.player_id == game.my_id && u.id != me.id)
+                            .any(|u| {
+                                let respawning_time = u.remaining_spawn_time.unwrap_or(0.0);
+                                let d = u.position.distance_to(&me.position);
+                                let seconds_to_unit =
                                     (d + self.constants.unit_radius) / weapon.projectile_speed;
 
-                                // debug_interface.add_poly_line(
-                                //     vec![aim.start, aim.end],
-                                //     0.1,
-                                //     Color::new(0.0, 0.0, 1.0, 0.5),
-                                // );
-                                // for o in self.constants.obstacles.iter().cloned() {
-                                //     let circle = Circle::new(o.position, o.radius);
-                                //     if circle.intercept_with_line(&aim) {
-                                //         debug_interface.add_circle(
-                                //             o.position,
-                                //             o.radius,
-                                //             Color::new(1.0, 0.0, 0.0, 0.5),
-                                //         );
-                                //     }
-                                // }
-                                let enemy_circle = Circle::new(
-                                    closest_enemy.position
-                                        + closest_enemy.velocity * seconds_to_enemy * 0.77,
-                                    self.constants.unit_radius * 0.9,
-                                );
-                                let obstacles_on_line = self
-                                    .constants
-                                    .obstacles
-                                    .iter()
-                                    .filter(|o| !o.can_shoot_through)
-                                    .filter(|o| o.as_circle(0.0).intercept_with_line(&aim))
-                                    .count();
-                                let unit_on_line = game
-                                    .units
-                                    .iter()
-                                    .filter(|u| u.player_id == game.my_id && u.id != me.id)
-                                    .any(|u| {
-                                        let respawning_time = u.remaining_spawn_time.unwrap_or(0.0);
-                                        let d = u.position.distance_to(&me.position);
-                                        let seconds_to_unit = (d + self.constants.unit_radius)
-                                            / weapon.projectile_speed;
+                                (seconds_to_unit + weapon.aim_time) > respawning_time
+                                    && u.as_circle(self.constants.unit_radius)
+                                        .intercept_with_line(&aim)
+                            });
 
-                                        (seconds_to_unit + weapon.aim_time) > respawning_time
-                                            && u.as_circle(self.constants.unit_radius)
-                                                .intercept_with_line(&aim)
-                                    });
+                        if obstacles_on_line > 1 || d > weapon_range || unit_on_line {
+                            AimDecision::Release
+                        } else {
+                            let remaining_spawn_time = target.remaining_spawn_time.unwrap_or(-1.0);
+                            let seconds_to_unspawned_enemy =
+                                (d - self.constants.unit_radius) / weapon.projectile_speed;
+                            let hit_model = &self.target_scorer.hit_model;
+                            let hit_probability = hit_model.probability(
+                                me,
+                                target,
+                                intercept,
+                                me.direction,
+                                &allies,
+                                &self.constants,
+                            );
+                            let aimed_hit_probability = hit_model.probability_when_aimed(
+                                me,
+                                target,
+                                intercept,
+                                &allies,
+                                &self.constants,
+                            );
+                            let quality = ShotQuality {
+                                now: hit_model.should_shoot(hit_probability, me, &self.constants)
+                                    && d <= (weapon_range + self.constants.unit_radius * 2.0)
+                                    && remaining_spawn_time < seconds_to_unspawned_enemy,
+                                aimed: hit_model.should_shoot(
+                                    aimed_hit_probability,
+                                    me,
+                                    &self.constants,
+                                ),
+                            };
+                            let retreating =
+                                (is_in_danger && behaviour.is_retreating()) || !policy.aim;
+                            self.aim_controller.decide(
+                                me,
+                                intercept,
+                                quality,
+                                retreating,
+                                game.current_tick,
+                                &self.constants,
+                            )
+                        }
+                    } else {
+                        AimDecision::Release
+                    };
 
-                                if obstacles_on_line > 1 || d > weapon_range || unit_on_line {
-                                    if let Some(loot) = self
-                                        .pp
-                                        .loot
-                                        .iter()
-                                        .filter(|l| {
-                                            l.position.distance_to(&me.position)
-                                                <= self.constants.unit_radius
-                                                && l.is_useful_to_me(me, &self.constants)
-                                        })
-                                        .next()
-                                    {
-                                        Some(ActionOrder::Pickup { loot: loot.id })
-                                    } else if me.shield
-                                        <= (self.constants.max_shield
-                                            - self.constants.shield_per_potion)
-                                        && me.shield_potions > 0
-                                    {
-                                        Some(ActionOrder::UseShieldPotion {})
-                                    } else {
-                                        None
-                                    }
-                                } else {
-                                    let remaining_spawn_time =
-                                        closest_enemy.remaining_spawn_time.unwrap_or(-1.0);
-                                    let seconds_to_unspawned_enemy =
-                                        (d - self.constants.unit_radius) / weapon.projectile_speed;
-                                    Some(ActionOrder::Aim {
-                                        shoot: obstacles_on_line == 0
-                                            && !unit_on_line
-                                            && enemy_circle.intercept_with_line(&aim)
-                                            && d <= (weapon_range
-                                                + self.constants.unit_radius * 2.0)
-                                            && remaining_spawn_time < seconds_to_unspawned_enemy,
-                                    })
-                                }
-                            } else if let Some(loot) = self
-                                .pp
-                                .loot
-                                .iter()
-                                .filter(|l| {
-                                    l.position.distance_to(&me.position)
-                                        <= self.constants.unit_radius
-                                        && l.is_useful_to_me(me, &self.constants)
-                                })
-                                .next()
-                            {
-                                Some(ActionOrder::Pickup { loot: loot.id })
-                            } else if me.shield
-                                <= (self.constants.max_shield - self.constants.shield_per_potion)
-                                && me.shield_potions > 0
-                            {
-                                Some(ActionOrder::UseShieldPotion {})
-                            } else {
-                                None
-                            },
+                    let action = match aim_decision {
+                        _ if drinking => Some(ActionOrder::UseShieldPotion {}),
+                        // any other action interrupts the potion
+                        aim_decision if potion == PotionDecision::Cancel && me.weapon.is_some() => {
+                            Some(ActionOrder::Aim {
+                                shoot: aim_decision == AimDecision::Fire,
+                            })
+                        }
+                        AimDecision::Fire => Some(ActionOrder::Aim { shoot: true }),
+                        AimDecision::Hold => Some(ActionOrder::Aim { shoot: false }),
+                        AimDecision::Release => idle_action(),
+                    };
+
+                    (
+                        me.id,
+                        UnitOrder {
+                            target_velocity,
+                            target_direction,
+                            action,
                         },
                     )
                 })
                 .collect(),
-        }
+        };
+        self.inventory.record(&order, game);
+        order
     }
     pub fn debug_update(&mut self, _displayed_tick: i32, _debug_interface: &mut DebugInterface) {}
     pub fn finish(&mut self) {}
 }
+
+fn draw_value_breakdown(
+    debug_interface: &mut DebugInterface,
+    position: Vec2,
+    breakdown: &ValueBreakdown,
+) {
+    for (i, line) in breakdown.lines().into_iter().enumerate() {
+        debug_interface.add_placed_text(
+            position - Vec2::new(0.0, i as f64 * 0.3),
+            line,
+            Vec2::new(0.0, 1.0),
+            0.25,
+            Color::BLACK,
+        );
+    }
+}
@@ -0,0 +1,113 @@
+/// What the unit is doing right now
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum Behaviour {
+    /// Landing after (re)spawn
+    Spawn,
+    /// Getting back inside the zone before it catches up
+    Reposition,
+    /// Running away to survive, shooting only at sure targets
+    Retreat,
+    /// Drinking shield potions out of enemies' reach
+    Heal,
+    /// Fighting with an advantage, closing to the effective range
+    Engage,
+    /// Fighting an out-gunning enemy, staying out of their range
+    Kite,
+    /// No fight around, collecting items
+    Loot,
+}
+
+/// Parent state of the behaviour, every group is handled by the same part of the strategy
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum BehaviourGroup {
+    Spawn,
+    Survive,
+    Fight,
+    Peace,
+}
+
+/// What kind of actions the behaviour allows
+#[derive(Copy, Clone, Debug)]
+pub struct ActionPolicy {
+    /// Start aiming at targets which are not sure hits yet
+    pub aim: bool,
+    /// Take loot lying under the unit
+    pub pickup: bool,
+    /// Drink shield potions
+    pub drink: bool,
+    /// Drop items for teammates
+    pub share: bool,
+}
+
+impl Behaviour {
+    pub const ALL: [Behaviour; 7] = [
+        Behaviour::Spawn,
+        Behaviour::Reposition,
+        Behaviour::Retreat,
+        Behaviour::Heal,
+        Behaviour::Engage,
+        Behaviour::Kite,
+        Behaviour::Loot,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Behaviour::Spawn => "Spawn",
+            Behaviour::Reposition => "Reposition",
+            Behaviour::Retreat => "Retreat",
+            Behaviour::Heal => "Heal",
+            Behaviour::Engage => "Engage",
+            Behaviour::Kite => "Kite",
+            Behaviour::Loot => "Loot",
+        }
+    }
+
+    pub fn group(&self) -> BehaviourGroup {
+        match self {
+            Behaviour::Spawn => BehaviourGroup::Spawn,
+            Behaviour::Reposition | Behaviour::Retreat | Behaviour::Heal => BehaviourGroup::Survive,
+            Behaviour::Engage | Behaviour::Kite => BehaviourGroup::Fight,
+            Behaviour::Loot => BehaviourGroup::Peace,
+        }
+    }
+
+    /// Whether the unit moves away from the enemies instead of towards them
+    pub fn is_retreating(&self) -> bool {
+        matches!(self, Behaviour::Retreat | Behaviour::Heal | Behaviour::Kite)
+    }
+
+    pub fn policy(&self) -> ActionPolicy {
+        match self {
+            Behaviour::Spawn => ActionPolicy {
+                aim: false,
+                pickup: false,
+                drink: false,
+                share: false,
+            },
+            Behaviour::Reposition | Behaviour::Retreat | Behaviour::Heal => ActionPolicy {
+                aim: false,
+                pickup: true,
+                drink: true,
+                share: false,
+            },
+            Behaviour::Engage | Behaviour::Kite => ActionPolicy {
+                aim: true,
+                pickup: true,
+                drink: true,
+                share: false,
+            },
+            Behaviour::Loot => ActionPolicy {
+                aim: true,
+                pickup: true,
+                drink: true,
+                share: true,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for Behaviour {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
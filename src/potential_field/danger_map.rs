@@ -0,0 +1,75 @@
+use crate::model::*;
+use crate::potential_field::{normalize_angle, TrackedEnemy};
+
+/// Seconds of turning after which the enemy's aim is considered half as dangerous
+const AIM_REACTION_TIME: f64 = 0.5;
+
+/// Whether there is an obstacle between the points which projectiles can't go through
+pub fn is_line_of_fire_blocked(from: Vec2, to: Vec2, constants: &Constants) -> bool {
+    let line = Line::new(from, to);
+    constants
+        .obstacles
+        .iter()
+        .filter(|o| !o.can_shoot_through)
+        .any(|o| o.as_circle(0.0).intercept_with_line(&line))
+}
+
+/// Whether there is an obstacle between the points which can't be seen through
+pub fn is_line_of_sight_blocked(from: Vec2, to: Vec2, constants: &Constants) -> bool {
+    let line = Line::new(from, to);
+    constants
+        .obstacles
+        .iter()
+        .filter(|o| !o.can_see_through)
+        .any(|o| o.as_circle(0.0).intercept_with_line(&line))
+}
+
+/// How much damage per second the enemy is able to deal to the position, relative to unit health.
+/// Positions out of the weapon range or behind obstacles which can't be shot through are safe,
+/// the further the enemy has to turn to aim at the position the safer it is.
+pub fn line_of_fire_danger(enemy: &TrackedEnemy, position: Vec2, constants: &Constants) -> f64 {
+    let unit = &enemy.unit;
+    let weapon_index = match unit.weapon {
+        Some(weapon) if unit.ammo[weapon as usize] > 0 => weapon as usize,
+        _ => return 0.0,
+    };
+    let weapon = &constants.weapons[weapon_index];
+
+    let distance = unit.position.distance_to(&position);
+    let range = weapon.range() + constants.unit_radius;
+    if distance > range {
+        return 0.0;
+    }
+
+    if is_line_of_fire_blocked(unit.position, position, constants) {
+        return 0.0;
+    }
+
+    let angle = normalize_angle((position - unit.position).angle() - unit.direction.angle())
+        .abs()
+        .to_degrees();
+    let rotation_speed = if unit.aim > 0.0 {
+        weapon.aim_rotation_speed
+    } else {
+        constants.rotation_speed
+    };
+    let seconds_to_aim = angle / rotation_speed
+        + weapon.aim_time * (1.0 - unit.aim)
+        + unit.remaining_spawn_time.unwrap_or(0.0);
+    let readiness = 1.0 / (1.0 + seconds_to_aim / AIM_REACTION_TIME);
+    let falloff = 0.5 + 0.5 * (1.0 - distance / range);
+
+    threat_strength(enemy, constants) * readiness * falloff
+}
+
+/// Damage per second of the enemy's weapon relative to unit health, or 0 if they can't shoot
+pub fn threat_strength(enemy: &TrackedEnemy, constants: &Constants) -> f64 {
+    match enemy.unit.weapon {
+        Some(weapon) if enemy.unit.ammo[weapon as usize] > 0 => {
+            let weapon = &constants.weapons[weapon as usize];
+            weapon.projectile_damage * weapon.rounds_per_second / constants.unit_health
+                * enemy.confidence
+        }
+        _ => 0.0,
+    }
+}
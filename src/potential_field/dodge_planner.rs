@@ -0,0 +1,151 @@
+use crate::model::*;
+use crate::potential_field::PotentialField;
+use std::f64::consts::PI;
+
+/// Projectile with the time it flies before vanishing or hitting an obstacle
+struct Threat<'a> {
+    projectile: &'a Projectile,
+    flight_time: f64,
+}
+
+/// Seconds until the projectile hits an obstacle it can't fly through, or its life time
+fn flight_time(projectile: &Projectile, constants: &Constants) -> f64 {
+    let speed = projectile.velocity.length();
+    if speed == 0.0 {
+        return projectile.life_time;
+    }
+    let dir = projectile.velocity / speed;
+    constants
+        .obstacles
+        .iter()
+        .filter(|o| !o.can_shoot_through)
+        .filter_map(|o| {
+            // entry point of the ray into the obstacle circle
+            let offset = o.position - projectile.position;
+            let along = offset.dot(&dir);
+            let square_miss = offset.dot(&offset) - along * along;
+            let square_radius = o.radius * o.radius;
+            if along < 0.0 || square_miss > square_radius {
+                return None;
+            }
+            Some((along - (square_radius - square_miss).sqrt()).max(0.0) / speed)
+        })
+        .fold(projectile.life_time, f64::min)
+}
+
+/// Finds the target velocity closest to the desired one which gets out of the way
+/// of all dangerous projectiles at once, simulating acceleration tick by tick
+#[derive(Clone, Debug)]
+pub struct DodgePlanner {
+    /// Number of moving directions tried
+    pub directions: usize,
+    /// Speeds tried in every direction, relative to the max forward speed
+    pub speeds: Vec<f64>,
+    /// Extra distance to keep from projectiles, in unit radii
+    pub margin: f64,
+    /// Projectiles further in the future are not dodged yet (seconds)
+    pub horizon: f64,
+}
+
+impl Default for DodgePlanner {
+    fn default() -> Self {
+        Self {
+            directions: 24,
+            speeds: vec![1.0, 0.5],
+            margin: 0.1,
+            horizon: 1.5,
+        }
+    }
+}
+
+impl DodgePlanner {
+    /// Damage the unit takes holding the target velocity
+    fn damage(&self, pp: &PotentialField, me: &Unit, target: Vec2, threats: &[Threat]) -> f64 {
+        let constants = pp.constants();
+        let obstacles = pp.obstacles();
+        let dt = 1.0 / constants.ticks_per_second;
+        let radius = constants.unit_radius * (1.0 + self.margin);
+        let horizon = threats
+            .iter()
+            .map(|t| t.flight_time)
+            .fold(0.0, f64::max)
+            .min(self.horizon);
+        let ticks = (horizon * constants.ticks_per_second).ceil() as usize;
+
+        let mut hit = vec![false; threats.len()];
+        let mut position = me.position;
+        let mut velocity = me.velocity;
+        for tick in 0..ticks {
+            velocity = me.next_velocity(velocity, target, constants);
+            let next = position + velocity * dt;
+            if obstacles.is_blocked(next) {
+                velocity = Vec2::zero();
+            } else {
+                position = next;
+            }
+
+            let seconds = tick as f64 * dt;
+            let circle = Circle::new(position, radius);
+            for (i, threat) in threats.iter().enumerate() {
+                if hit[i] || seconds >= threat.flight_time {
+                    continue;
+                }
+                let step = dt.min(threat.flight_time - seconds);
+                hit[i] = threat
+                    .projectile
+                    .advanced(seconds)
+                    .hits_circle_within(&circle, step);
+            }
+        }
+
+        threats
+            .iter()
+            .zip(hit)
+            .filter(|(_, hit)| *hit)
+            .map(|(t, _)| {
+                constants.weapons[t.projectile.weapon_type_index as usize].projectile_damage
+            })
+            .sum()
+    }
+
+    /// Target velocity to dodge with, or None if the desired one is already safe
+    pub fn plan(&self, pp: &PotentialField, me: &Unit, desired: Vec2) -> Option<Vec2> {
+        if me.remaining_spawn_time.is_some() {
+            return None;
+        }
+        let constants = pp.constants();
+        let threats = pp
+            .dangerous_projectiles
+            .iter()
+            .filter(|p| p.is_dangerous(me, constants))
+            .map(|p| Threat {
+                projectile: p,
+                flight_time: flight_time(p, constants),
+            })
+            .collect::<Vec<_>>();
+        if threats.is_empty() || self.damage(pp, me, desired, &threats) == 0.0 {
+            return None;
+        }
+
+        let mut candidates = vec![Vec2::zero()];
+        for &speed in self.speeds.iter() {
+            candidates.extend((0..self.directions).map(|i| {
+                let angle = i as f64 * 2.0 * PI / self.directions as f64;
+                Vec2::new(angle.cos(), angle.sin()) * (speed * constants.max_unit_forward_speed)
+            }));
+        }
+
+        candidates
+            .into_iter()
+            .map(|v| {
+                let damage = self.damage(pp, me, v, &threats);
+                (v, damage, (v - desired).length())
+            })
+            .min_by(|(_, a_damage, a_deviation), (_, b_damage, b_deviation)| {
+                a_damage
+                    .total_cmp(b_damage)
+                    .then(a_deviation.total_cmp(b_deviation))
+            })
+            .map(|(v, _, _)| v)
+    }
+}
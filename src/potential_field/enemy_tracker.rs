@@ -0,0 +1,199 @@
+use crate::model::*;
+use crate::potential_field::is_line_of_sight_blocked;
+use std::collections::HashMap;
+
+/// Confidence multiplier for every tick the enemy is not seen
+const CONFIDENCE_DECAY: f64 = 0.99;
+/// Unseen enemy velocity multiplier per tick, they are likely to turn or stop
+const VELOCITY_DECAY: f64 = 0.95;
+/// Estimates with lower confidence are forgotten
+const MIN_CONFIDENCE: f64 = 0.1;
+
+/// What we know about an enemy unit
+#[derive(Clone, Debug)]
+pub struct TrackedEnemy {
+    /// Last seen state with extrapolated position and estimated health, shield and ammo
+    pub unit: Unit,
+    /// Tick when the enemy was seen last time
+    pub last_seen_tick: i32,
+    /// Enemy is somewhere inside the circle around estimated position
+    pub uncertainty: f64,
+    /// Probability that the enemy is still around estimated position
+    pub confidence: f64,
+}
+
+impl TrackedEnemy {
+    fn new(unit: &Unit, tick: i32) -> Self {
+        Self {
+            unit: unit.clone(),
+            last_seen_tick: tick,
+            uncertainty: 0.0,
+            confidence: 1.0,
+        }
+    }
+
+    pub fn is_visible(&self, tick: i32) -> bool {
+        self.last_seen_tick == tick
+    }
+
+    pub fn could_be_at(&self, position: Vec2, tolerance: f64) -> bool {
+        self.unit.position.distance_to(&position) <= self.uncertainty + tolerance
+    }
+
+    fn take_damage(&mut self, damage: f64) {
+        let shield_damage = damage.min(self.unit.shield);
+        self.unit.shield -= shield_damage;
+        self.unit.health = (self.unit.health - (damage - shield_damage)).max(0.0);
+    }
+}
+
+pub struct EnemyTracker {
+    constants: Constants,
+    enemies: HashMap<i32, TrackedEnemy>,
+    current_tick: i32,
+}
+
+impl EnemyTracker {
+    pub fn new(constants: &Constants) -> Self {
+        Self {
+            constants: constants.clone(),
+            enemies: HashMap::new(),
+            current_tick: 0,
+        }
+    }
+
+    pub fn update(&mut self, game: &Game) {
+        self.current_tick = game.current_tick;
+        for unit in game.units.iter().filter(|u| u.player_id != game.my_id) {
+            self.enemies
+                .insert(unit.id, TrackedEnemy::new(unit, game.current_tick));
+        }
+
+        let dt = 1.0 / self.constants.ticks_per_second;
+        let max_speed = self.constants.max_unit_forward_speed;
+        let unit_health = self.constants.unit_health;
+        let regeneration = self.constants.health_regeneration_per_second * dt;
+        for enemy in self
+            .enemies
+            .values_mut()
+            .filter(|e| !e.is_visible(game.current_tick))
+        {
+            enemy.unit.velocity = enemy.unit.velocity * VELOCITY_DECAY;
+            enemy.unit.position += enemy.unit.velocity * dt;
+            enemy.uncertainty += max_speed * dt;
+            enemy.confidence *= CONFIDENCE_DECAY;
+            if let Some(remaining_spawn_time) = enemy.unit.remaining_spawn_time {
+                enemy.unit.remaining_spawn_time = if remaining_spawn_time > dt {
+                    Some(remaining_spawn_time - dt)
+                } else {
+                    None
+                };
+            }
+            if game.current_tick >= enemy.unit.health_regeneration_start_tick {
+                enemy.unit.health = (enemy.unit.health + regeneration).min(unit_health);
+            }
+        }
+
+        for sound in game.sounds.iter() {
+            self.apply_sound(sound, game);
+        }
+
+        // enemy is not where we expected it to be, unless something hides it from us
+        let constants = &self.constants;
+        for enemy in self
+            .enemies
+            .values_mut()
+            .filter(|e| !e.is_visible(game.current_tick))
+        {
+            if game
+                .units
+                .iter()
+                .filter(|u| u.player_id == game.my_id)
+                .any(|u| {
+                    u.is_in_fov(enemy.unit.position, constants)
+                        && !is_line_of_sight_blocked(u.position, enemy.unit.position, constants)
+                })
+            {
+                enemy.confidence *= 0.5;
+            }
+        }
+        self.enemies.retain(|_, e| e.confidence >= MIN_CONFIDENCE);
+    }
+
+    /// Sounds which can't be made by visible units are evidence for remembered enemies
+    fn apply_sound(&mut self, sound: &Sound, game: &Game) {
+        let props = &self.constants.sounds[sound.type_index as usize];
+        let tolerance = self.constants.unit_radius + props.offset;
+        if game
+            .units
+            .iter()
+            .any(|u| u.position.distance_to(&sound.position) <= tolerance)
+        {
+            return;
+        }
+
+        let shot_weapon = self
+            .constants
+            .weapons
+            .iter()
+            .position(|w| w.shot_sound_type_index == Some(sound.type_index));
+        let hit_weapon = self
+            .constants
+            .weapons
+            .iter()
+            .position(|w| w.projectile_hit_sound_type_index == Some(sound.type_index));
+        let is_steps = self.constants.steps_sound_type_index == Some(sound.type_index);
+        if shot_weapon.is_none() && hit_weapon.is_none() && !is_steps {
+            return;
+        }
+
+        let tick = self.current_tick;
+        let enemy = self
+            .enemies
+            .values_mut()
+            .filter(|e| !e.is_visible(tick) && e.could_be_at(sound.position, tolerance))
+            .min_by(|a, b| {
+                a.unit
+                    .position
+                    .square_distance_to(&sound.position)
+                    .total_cmp(&b.unit.position.square_distance_to(&sound.position))
+            });
+        let enemy = match enemy {
+            Some(enemy) => enemy,
+            None => return,
+        };
+
+        if let Some(weapon) = hit_weapon {
+            enemy.take_damage(self.constants.weapons[weapon].projectile_damage);
+            return;
+        }
+
+        if enemy.uncertainty > props.offset {
+            // the source is somewhere inside the offset circle around heard position
+            let k = props.offset / enemy.uncertainty;
+            enemy.unit.position = sound.position + (enemy.unit.position - sound.position) * k;
+            enemy.uncertainty = props.offset;
+        }
+        enemy.confidence = enemy.confidence.max(0.8);
+        if let Some(weapon) = shot_weapon {
+            enemy.unit.weapon = Some(weapon as i32);
+            // the weapon may be new to us, it was loaded for the shot at least
+            let ammo = &mut enemy.unit.ammo[weapon];
+            *ammo = (*ammo - 1).max(1);
+        }
+    }
+
+    pub fn get(&self, id: i32) -> Option<&TrackedEnemy> {
+        self.enemies.get(&id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TrackedEnemy> {
+        self.enemies.values()
+    }
+
+    /// Enemies which are not visible right now
+    pub fn remembered(&self) -> impl Iterator<Item = &TrackedEnemy> {
+        let tick = self.current_tick;
+        self.enemies.values().filter(move |e| !e.is_visible(tick))
+    }
+}
@@ -0,0 +1,146 @@
+use crate::model::*;
+use crate::potential_field::{Behaviour, GridArea, PotentialField};
+use ndarray::Array2;
+use ndarray_npy::write_npy;
+use std::path::PathBuf;
+
+/// Settings of the `.npy` dump, disabled unless `AI_CUP_DUMP_DIR` is set
+#[derive(Clone, Debug)]
+pub struct DumpConfig {
+    /// Directory for `.npy` files
+    pub dir: PathBuf,
+    /// Dump every N ticks
+    pub every_ticks: i32,
+    /// Distance between grid cells
+    pub step: f64,
+    /// Half size of the window around each unit, or None for the whole zone
+    pub half_size: Option<f64>,
+}
+
+impl DumpConfig {
+    pub fn from_env() -> Option<Self> {
+        let dir = std::env::var("AI_CUP_DUMP_DIR").ok()?;
+        let every_ticks = std::env::var("AI_CUP_DUMP_EVERY")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(10)
+            .max(1);
+        let step = std::env::var("AI_CUP_DUMP_STEP")
+            .ok()
+            .and_then(|s| s.parse::<f64>().ok())
+            .filter(|step| step.is_finite() && *step > 0.0)
+            .unwrap_or(1.0);
+        let half_size = std::env::var("AI_CUP_DUMP_WINDOW")
+            .ok()
+            .and_then(|s| s.parse().ok());
+
+        Some(Self {
+            dir: PathBuf::from(dir),
+            every_ticks,
+            step,
+            half_size,
+        })
+    }
+}
+
+pub struct FieldDumper {
+    config: DumpConfig,
+}
+
+impl FieldDumper {
+    pub fn new(config: DumpConfig) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(&config.dir)?;
+        Ok(Self { config })
+    }
+
+    pub fn is_dump_tick(&self, tick: i32) -> bool {
+        tick % self.config.every_ticks == 0
+    }
+
+    fn path(&self, name: String) -> PathBuf {
+        self.config.dir.join(format!("{}.npy", name))
+    }
+
+    /// Units and projectiles seen this tick, one row per object
+    pub fn dump_game(&self, game: &Game) -> anyhow::Result<()> {
+        let units = Array2::from_shape_fn((game.units.len(), 10), |(i, j)| {
+            let unit = &game.units[i];
+            match j {
+                0 => unit.id as f64,
+                1 => unit.player_id as f64,
+                2 => unit.position.x,
+                3 => unit.position.y,
+                4 => unit.velocity.x,
+                5 => unit.velocity.y,
+                6 => unit.health,
+                7 => unit.shield,
+                8 => unit.weapon.map_or(-1.0, |w| w as f64),
+                _ => unit.aim,
+            }
+        });
+        write_npy(
+            self.path(format!("tick_{:05}_units", game.current_tick)),
+            &units,
+        )?;
+
+        let projectiles = Array2::from_shape_fn((game.projectiles.len(), 7), |(i, j)| {
+            let projectile = &game.projectiles[i];
+            match j {
+                0 => projectile.id as f64,
+                1 => projectile.shooter_player_id as f64,
+                2 => projectile.position.x,
+                3 => projectile.position.y,
+                4 => projectile.velocity.x,
+                5 => projectile.velocity.y,
+                _ => projectile.life_time,
+            }
+        });
+        write_npy(
+            self.path(format!("tick_{:05}_projectiles", game.current_tick)),
+            &projectiles,
+        )?;
+
+        Ok(())
+    }
+
+    /// Weighted value of every active term for the unit, their sum as `total` and the
+    /// `[origin.x, origin.y, step]` of the grids
+    pub fn dump_unit(
+        &self,
+        pp: &PotentialField,
+        tick: i32,
+        me: &Unit,
+        behaviour: Behaviour,
+    ) -> anyhow::Result<()> {
+        let area = match self.config.half_size {
+            Some(half_size) => GridArea::Window {
+                center: me.position,
+                half_size,
+            },
+            None => GridArea::Zone,
+        };
+
+        for term in pp.active_terms(me, behaviour).iter() {
+            let term_grid = pp.rasterize_with(area, self.config.step, |position| {
+                term.evaluate(pp, position, me, behaviour) * term.weight()
+            });
+            write_npy(
+                self.path(format!("tick_{:05}_unit_{}_{}", tick, me.id, term.name())),
+                &term_grid.values,
+            )?;
+        }
+
+        let grid = pp.rasterize(me, behaviour, area, self.config.step);
+        write_npy(
+            self.path(format!("tick_{:05}_unit_{}_total", tick, me.id)),
+            &grid.values,
+        )?;
+        let meta = ndarray::arr1(&[grid.origin.x, grid.origin.y, grid.step]);
+        write_npy(
+            self.path(format!("tick_{:05}_unit_{}_grid", tick, me.id)),
+            &meta,
+        )?;
+
+        Ok(())
+    }
+}
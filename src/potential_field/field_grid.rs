@@ -0,0 +1,128 @@
+use crate::model::Vec2;
+use ndarray::Array2;
+
+/// Area of the map to rasterise
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GridArea {
+    /// Square window around the point
+    Window { center: Vec2, half_size: f64 },
+    /// Bounding square of the current zone
+    Zone,
+}
+
+/// Field values sampled on a regular grid
+#[derive(Clone, Debug)]
+pub struct FieldGrid {
+    /// Position of the `[0, 0]` cell
+    pub origin: Vec2,
+    /// Distance between neighbour cells
+    pub step: f64,
+    /// Sampled values, indexed as `[x, y]`
+    pub values: Array2<f64>,
+}
+
+impl FieldGrid {
+    pub fn from_fn<F>(center: Vec2, half_size: f64, step: f64, f: F) -> Self
+    where
+        F: Fn(Vec2) -> f64,
+    {
+        let cells = (half_size * 2.0 / step).ceil().max(1.0) as usize + 1;
+        let origin = Vec2::new(center.x - half_size, center.y - half_size);
+        let values = Array2::from_shape_fn((cells, cells), |(x, y)| {
+            f(Vec2::new(
+                origin.x + x as f64 * step,
+                origin.y + y as f64 * step,
+            ))
+        });
+
+        Self {
+            origin,
+            step,
+            values,
+        }
+    }
+
+    pub fn cell_position(&self, x: usize, y: usize) -> Vec2 {
+        Vec2::new(
+            self.origin.x + x as f64 * self.step,
+            self.origin.y + y as f64 * self.step,
+        )
+    }
+
+    pub fn contains(&self, position: Vec2) -> bool {
+        let (width, height) = self.values.dim();
+        let x = (position.x - self.origin.x) / self.step;
+        let y = (position.y - self.origin.y) / self.step;
+        x >= 0.0 && y >= 0.0 && x <= (width - 1) as f64 && y <= (height - 1) as f64
+    }
+
+    /// Bilinear interpolation between four nearest cells
+    pub fn sample(&self, position: Vec2) -> Option<f64> {
+        if !self.contains(position) {
+            return None;
+        }
+        let (width, height) = self.values.dim();
+        let x = (position.x - self.origin.x) / self.step;
+        let y = (position.y - self.origin.y) / self.step;
+        let x0 = (x.floor() as usize).min(width - 1);
+        let y0 = (y.floor() as usize).min(height - 1);
+        let x1 = (x0 + 1).min(width - 1);
+        let y1 = (y0 + 1).min(height - 1);
+        let dx = x - x0 as f64;
+        let dy = y - y0 as f64;
+
+        let bottom = self.values[[x0, y0]] * (1.0 - dx) + self.values[[x1, y0]] * dx;
+        let top = self.values[[x0, y1]] * (1.0 - dx) + self.values[[x1, y1]] * dx;
+        Some(bottom * (1.0 - dy) + top * dy)
+    }
+
+    pub fn best_cell(&self) -> Option<(Vec2, f64)> {
+        self.values
+            .indexed_iter()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|((x, y), value)| (self.cell_position(x, y), *value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plane() -> FieldGrid {
+        // value is x + 10 * y, bilinear interpolation is exact for it
+        FieldGrid::from_fn(Vec2::new(5.0, 5.0), 2.0, 1.0, |p| p.x + 10.0 * p.y)
+    }
+
+    #[test]
+    fn cells_are_indexed_by_x_then_y() {
+        let grid = plane();
+        assert_eq!(grid.values.dim(), (5, 5));
+        assert_eq!(grid.origin, Vec2::new(3.0, 3.0));
+        assert_eq!(grid.cell_position(1, 3), Vec2::new(4.0, 6.0));
+        assert_eq!(grid.values[[1, 3]], 4.0 + 60.0);
+    }
+
+    #[test]
+    fn sample_interpolates_between_cells() {
+        let grid = plane();
+        let value = grid.sample(Vec2::new(4.25, 5.5)).unwrap();
+        assert!((value - (4.25 + 55.0)).abs() < 1e-9);
+        let corner = grid.sample(Vec2::new(7.0, 7.0)).unwrap();
+        assert!((corner - 77.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sample_outside_is_none() {
+        let grid = plane();
+        assert!(grid.contains(Vec2::new(3.0, 7.0)));
+        assert!(grid.sample(Vec2::new(2.9, 5.0)).is_none());
+        assert!(grid.sample(Vec2::new(5.0, 7.1)).is_none());
+    }
+
+    #[test]
+    fn best_cell_is_the_max() {
+        let (position, value) = plane().best_cell().unwrap();
+        assert_eq!(position, Vec2::new(7.0, 7.0));
+        assert_eq!(value, 77.0);
+    }
+}
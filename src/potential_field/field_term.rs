@@ -0,0 +1,163 @@
+use crate::model::*;
+use crate::potential_field::{Behaviour, PotentialField};
+use std::fmt;
+
+/// Single weighted component of the potential field
+pub trait FieldTerm {
+    fn name(&self) -> &'static str;
+    fn weight(&self) -> f64;
+    fn set_weight(&mut self, weight: f64);
+    /// Raw (not weighted) value of the term at the position
+    fn evaluate(&self, pp: &PotentialField, position: Vec2, me: &Unit, behaviour: Behaviour)
+        -> f64;
+    /// Raw value after given seconds from the current tick, terms which don't change in time
+    /// can keep the default
+    fn evaluate_at(
+        &self,
+        pp: &PotentialField,
+        position: Vec2,
+        me: &Unit,
+        behaviour: Behaviour,
+        _seconds: f64,
+    ) -> f64 {
+        self.evaluate(pp, position, me, behaviour)
+    }
+}
+
+/// Contribution of a single term at the point
+#[derive(Clone, Debug)]
+pub struct TermContribution {
+    pub name: &'static str,
+    pub raw: f64,
+    pub weight: f64,
+    pub weighted: f64,
+}
+
+/// Field value split by terms
+#[derive(Clone, Debug)]
+pub struct ValueBreakdown {
+    pub terms: Vec<TermContribution>,
+    pub total: f64,
+}
+
+impl ValueBreakdown {
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = self
+            .terms
+            .iter()
+            .map(|t| {
+                format!(
+                    "{}: {:.2} x {:.1} = {:.2}",
+                    t.name, t.raw, t.weight, t.weighted
+                )
+            })
+            .collect::<Vec<String>>();
+        lines.push(format!("total: {:.2}", self.total));
+        lines
+    }
+}
+
+impl fmt::Display for ValueBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.lines().join("\n"))
+    }
+}
+
+/// Set of terms summed into the field value
+#[derive(Default)]
+pub struct FieldTerms {
+    terms: Vec<Box<dyn FieldTerm>>,
+}
+
+impl FieldTerms {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with<T: FieldTerm + 'static>(mut self, term: T) -> Self {
+        self.add(Box::new(term));
+        self
+    }
+
+    pub fn add(&mut self, term: Box<dyn FieldTerm>) {
+        self.terms.push(term);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Box<dyn FieldTerm>> {
+        let index = self.terms.iter().position(|t| t.name() == name)?;
+        Some(self.terms.remove(index))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn FieldTerm> {
+        self.terms
+            .iter()
+            .find(|t| t.name() == name)
+            .map(|t| t.as_ref())
+    }
+
+    /// Returns false if there is no term with such name
+    pub fn set_weight(&mut self, name: &str, weight: f64) -> bool {
+        if let Some(term) = self.terms.iter_mut().find(|t| t.name() == name) {
+            term.set_weight(weight);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn FieldTerm> {
+        self.terms.iter().map(|t| t.as_ref())
+    }
+
+    pub fn value(
+        &self,
+        pp: &PotentialField,
+        position: Vec2,
+        me: &Unit,
+        behaviour: Behaviour,
+    ) -> f64 {
+        self.terms
+            .iter()
+            .map(|t| t.evaluate(pp, position, me, behaviour) * t.weight())
+            .sum()
+    }
+
+    pub fn value_at(
+        &self,
+        pp: &PotentialField,
+        position: Vec2,
+        me: &Unit,
+        behaviour: Behaviour,
+        seconds: f64,
+    ) -> f64 {
+        self.terms
+            .iter()
+            .map(|t| t.evaluate_at(pp, position, me, behaviour, seconds) * t.weight())
+            .sum()
+    }
+
+    pub fn breakdown(
+        &self,
+        pp: &PotentialField,
+        position: Vec2,
+        me: &Unit,
+        behaviour: Behaviour,
+    ) -> ValueBreakdown {
+        let terms = self
+            .terms
+            .iter()
+            .map(|t| {
+                let raw = t.evaluate(pp, position, me, behaviour);
+                TermContribution {
+                    name: t.name(),
+                    raw,
+                    weight: t.weight(),
+                    weighted: raw * t.weight(),
+                }
+            })
+            .collect::<Vec<TermContribution>>();
+        let total = terms.iter().map(|t| t.weighted).sum();
+
+        ValueBreakdown { terms, total }
+    }
+}
@@ -0,0 +1,179 @@
+use crate::model::*;
+use crate::potential_field::{Behaviour, FieldTerm, PotentialField};
+
+/// Raw value of a term at the position
+pub type TermFn = fn(&PotentialField, Vec2, &Unit, Behaviour) -> f64;
+/// Raw value of a term after given seconds from the current tick
+pub type TermAtFn = fn(&PotentialField, Vec2, &Unit, Behaviour, f64) -> f64;
+
+/// Term backed by a value function of the potential field
+#[derive(Clone, Copy)]
+pub struct FnTerm {
+    pub name: &'static str,
+    pub weight: f64,
+    pub f: TermFn,
+    /// Time-dependent value, None if the term doesn't change in time
+    pub f_at: Option<TermAtFn>,
+}
+
+impl FnTerm {
+    /// Same term with another weight
+    pub fn weighted(self, weight: f64) -> Self {
+        Self { weight, ..self }
+    }
+}
+
+impl FieldTerm for FnTerm {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    fn set_weight(&mut self, weight: f64) {
+        self.weight = weight;
+    }
+
+    fn evaluate(
+        &self,
+        pp: &PotentialField,
+        position: Vec2,
+        me: &Unit,
+        behaviour: Behaviour,
+    ) -> f64 {
+        (self.f)(pp, position, me, behaviour)
+    }
+
+    fn evaluate_at(
+        &self,
+        pp: &PotentialField,
+        position: Vec2,
+        me: &Unit,
+        behaviour: Behaviour,
+        seconds: f64,
+    ) -> f64 {
+        match self.f_at {
+            Some(f_at) => f_at(pp, position, me, behaviour, seconds),
+            None => (self.f)(pp, position, me, behaviour),
+        }
+    }
+}
+
+/// Dangerous projectiles, being on their way is penalized
+pub const PROJECTILES: FnTerm = FnTerm {
+    name: "projectiles",
+    weight: 3.0,
+    f: |pp, position, _, _| pp.value_projectiles(position),
+    f_at: Some(|pp, position, _, _, seconds| pp.value_projectiles_at(position, seconds)),
+};
+
+/// Attraction to the center of the next zone
+pub const ZONE: FnTerm = FnTerm {
+    name: "zone",
+    weight: 1.0,
+    f: |pp, position, _, _| pp.value_zone(position),
+    f_at: None,
+};
+
+/// Penalty for being near the edge of the current zone
+pub const OUTSIDE: FnTerm = FnTerm {
+    name: "outside",
+    weight: 5.0,
+    f: |pp, position, _, _| pp.value_outside(position),
+    f_at: Some(|pp, position, _, _, seconds| pp.value_outside_at(position, seconds)),
+};
+
+/// Lines between recent shots and our units
+pub const SHOOTING_SOUNDS: FnTerm = FnTerm {
+    name: "shooting_sounds",
+    weight: 1.0,
+    f: |pp, position, _, _| pp.value_shooting_sounds(position),
+    f_at: None,
+};
+
+/// Places of recent hits
+pub const HIT_SOUNDS: FnTerm = FnTerm {
+    name: "hit_sounds",
+    weight: 1.0,
+    f: |pp, position, _, _| pp.value_hit_sounds(position),
+    f_at: None,
+};
+
+/// Places of recent steps
+pub const STEPS_SOUNDS: FnTerm = FnTerm {
+    name: "steps_sounds",
+    weight: 1.0,
+    f: |pp, position, _, _| pp.value_steps_sounds(position),
+    f_at: None,
+};
+
+/// Seen and remembered enemies, depends on the fight mode
+pub const ENEMIES: FnTerm = FnTerm {
+    name: "enemies",
+    weight: 1.0,
+    f: |pp, position, me, behaviour| pp.value_enemies(position, me, behaviour),
+    f_at: None,
+};
+
+/// Line of fire of enemies' weapons
+pub const DANGER: FnTerm = FnTerm {
+    name: "danger",
+    weight: 1.0,
+    f: |pp, position, _, _| pp.value_danger(position),
+    f_at: None,
+};
+
+/// Positions shielded from known threats by obstacles
+pub const COVER: FnTerm = FnTerm {
+    name: "cover",
+    weight: 1.0,
+    f: |pp, position, _, _| pp.value_cover(position),
+    f_at: None,
+};
+
+/// Range and line of fire to the target assigned by the team coordinator
+pub const TARGET: FnTerm = FnTerm {
+    name: "target",
+    weight: 1.0,
+    f: |pp, position, me, _| pp.value_target(position, me),
+    f_at: None,
+};
+
+/// Keeping distance to allies
+pub const ALLIES: FnTerm = FnTerm {
+    name: "allies",
+    weight: 1.0,
+    f: |pp, position, me, _| pp.value_allies(position, me),
+    f_at: None,
+};
+
+/// Attraction to useful loot
+pub const LOOT: FnTerm = FnTerm {
+    name: "loot",
+    weight: 1.0,
+    f: |pp, position, me, _| pp.value_loot(position, me),
+    f_at: None,
+};
+
+/// Every known term with its default weight
+pub const TERMS: [FnTerm; 12] = [
+    PROJECTILES,
+    ZONE,
+    OUTSIDE,
+    SHOOTING_SOUNDS,
+    HIT_SOUNDS,
+    STEPS_SOUNDS,
+    ENEMIES,
+    DANGER,
+    COVER,
+    TARGET,
+    ALLIES,
+    LOOT,
+];
+
+/// Registered term by its name
+pub fn term(name: &str) -> Option<FnTerm> {
+    TERMS.iter().find(|t| t.name == name).copied()
+}
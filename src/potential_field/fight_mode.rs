@@ -1,6 +0,0 @@
-#[derive(Copy, Clone, Debug)]
-pub enum FightMode {
-    Attack,
-    Defend,
-    RunWithNoWeapons,
-}
@@ -0,0 +1,182 @@
+use crate::model::*;
+use crate::potential_field::EnemyTracker;
+use std::collections::{HashMap, HashSet};
+
+/// Loot which is probably taken is not worth running to
+const MAX_TAKEN_PROBABILITY: f64 = 0.4;
+/// Loot which is almost certainly taken is forgotten
+const FORGET_TAKEN_PROBABILITY: f64 = 0.9;
+/// Seconds after which an enemy who reached the loot has surely picked it up
+const ENEMY_LOOTING_WINDOW: f64 = 1.5;
+/// Probability that an enemy nearby wants this loot at all
+const ENEMY_INTEREST: f64 = 0.9;
+/// Mean seconds before the loot is picked up by somebody we don't know about
+const UNKNOWN_LOOTER_TIME: f64 = 60.0;
+
+/// Loot seen before with the estimate of it being still there
+#[derive(Clone, Debug)]
+pub struct RememberedLoot {
+    pub loot: Loot,
+    /// Tick when the loot was seen last time
+    pub last_seen_tick: i32,
+    /// Probability that somebody has already picked it up
+    pub taken_probability: f64,
+}
+
+impl RememberedLoot {
+    pub fn is_available(&self) -> bool {
+        self.taken_probability < MAX_TAKEN_PROBABILITY
+    }
+}
+
+pub struct LootMemory {
+    constants: Constants,
+    items: HashMap<i32, RememberedLoot>,
+}
+
+impl LootMemory {
+    pub fn new(constants: &Constants) -> Self {
+        Self {
+            constants: constants.clone(),
+            items: HashMap::new(),
+        }
+    }
+
+    pub fn update(&mut self, game: &Game, enemies: &EnemyTracker) {
+        let seeing_ids = game.loot.iter().map(|l| l.id).collect::<HashSet<i32>>();
+        let constants = &self.constants;
+        self.items.retain(|id, item| {
+            seeing_ids.contains(id)
+                || !game
+                    .units
+                    .iter()
+                    .filter(|u| u.player_id == game.my_id)
+                    .any(|u| u.is_in_fov(item.loot.position, constants))
+        });
+        for loot in game.loot.iter() {
+            self.items.insert(
+                loot.id,
+                RememberedLoot {
+                    loot: loot.clone(),
+                    last_seen_tick: game.current_tick,
+                    taken_probability: 0.0,
+                },
+            );
+        }
+
+        for item in self
+            .items
+            .values_mut()
+            .filter(|i| i.last_seen_tick != game.current_tick)
+        {
+            let elapsed =
+                (game.current_tick - item.last_seen_tick) as f64 / constants.ticks_per_second;
+            // anybody could have come by while we weren't looking
+            let not_taken_by_unknown = (-elapsed / UNKNOWN_LOOTER_TIME).exp();
+            // only enemies who had enough time to walk there since we saw the loot count
+            let not_taken_by_enemies = enemies
+                .iter()
+                .filter_map(|enemy| {
+                    let distance = (enemy.unit.position.distance_to(&item.loot.position)
+                        - constants.unit_radius)
+                        .max(0.0);
+                    let time_to_take =
+                        distance / constants.max_unit_forward_speed + constants.looting_time;
+                    (elapsed >= time_to_take).then(|| {
+                        let k = ((elapsed - time_to_take) / ENEMY_LOOTING_WINDOW).min(1.0);
+                        1.0 - k * enemy.confidence * ENEMY_INTEREST
+                    })
+                })
+                .product::<f64>();
+            // enemies walking away don't put the loot back
+            item.taken_probability = item
+                .taken_probability
+                .max(1.0 - not_taken_by_unknown * not_taken_by_enemies);
+        }
+
+        self.items
+            .retain(|_, i| i.taken_probability < FORGET_TAKEN_PROBABILITY);
+    }
+
+    pub fn get(&self, id: i32) -> Option<&RememberedLoot> {
+        self.items.get(&id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RememberedLoot> {
+        self.items.values()
+    }
+
+    /// Loot which is likely still lying on the ground
+    pub fn available(&self) -> impl Iterator<Item = &Loot> {
+        self.items
+            .values()
+            .filter(|i| i.is_available())
+            .map(|i| &i.loot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(tick: i32, units: Vec<Unit>, loot: Vec<Loot>) -> Game {
+        Game {
+            my_id: 0,
+            players: Vec::new(),
+            current_tick: tick,
+            units,
+            loot,
+            projectiles: Vec::new(),
+            zone: Zone::default(),
+            sounds: Vec::new(),
+        }
+    }
+
+    fn potions(position: Vec2) -> Loot {
+        Loot {
+            id: 1,
+            position,
+            item: Item::ShieldPotions { amount: 1 },
+        }
+    }
+
+    /// Sees the loot, then the enemy, then looks away from both until the given tick
+    fn run(enemy: Option<Unit>, ticks: i32) -> LootMemory {
+        let constants = test_constants();
+        let mut memory = LootMemory::new(&constants);
+        let mut tracker = EnemyTracker::new(&constants);
+        let mut me = test_unit(1, 0, Vec2::zero());
+        let item = potions(Vec2::new(20.0, 0.0));
+        let first = game(0, vec![me.clone()], vec![item]);
+        tracker.update(&first);
+        memory.update(&first, &tracker);
+
+        me.direction = Vec2::new(-1.0, 0.0);
+        let mut units = vec![me];
+        units.extend(enemy);
+        let seen = game(1, units, Vec::new());
+        tracker.update(&seen);
+        memory.update(&seen, &tracker);
+        for tick in 2..=ticks {
+            let game = game(tick, seen.units[..1].to_vec(), Vec::new());
+            tracker.update(&game);
+            memory.update(&game, &tracker);
+        }
+        memory
+    }
+
+    #[test]
+    fn single_enemy_takes_loot() {
+        let enemy = test_unit(2, 1, Vec2::new(25.0, 0.0));
+        let memory = run(Some(enemy), 90);
+        assert!(memory.available().next().is_none());
+    }
+
+    #[test]
+    fn loot_without_enemies_fades_with_time() {
+        let ticks_per_minute = 60 * 30;
+        assert!(run(None, 30).available().next().is_some());
+        assert!(run(None, ticks_per_minute).available().next().is_none());
+        assert!(run(None, 3 * ticks_per_minute).get(1).is_none());
+    }
+}
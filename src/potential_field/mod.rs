@@ -1,5 +1,28 @@
-mod fight_mode;
+mod behaviour;
+mod danger_map;
+mod dodge_planner;
+mod enemy_tracker;
+mod field_dump;
+mod field_grid;
+mod field_term;
+mod field_terms;
+mod loot_memory;
+mod obstacle_index;
+#[allow(clippy::module_inception)]
 mod potential_field;
+mod spawn_planner;
+mod trajectory_planner;
 
-pub use fight_mode::*;
+pub use behaviour::*;
+pub use danger_map::*;
+pub use dodge_planner::*;
+pub use enemy_tracker::*;
+pub use field_dump::*;
+pub use field_grid::*;
+pub use field_term::*;
+pub use field_terms::*;
+pub use loot_memory::*;
+pub use obstacle_index::*;
 pub use potential_field::*;
+pub use spawn_planner::*;
+pub use trajectory_planner::*;
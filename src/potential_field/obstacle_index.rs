@@ -0,0 +1,133 @@
+use crate::model::*;
+
+/// Obstacles bucketed by a square grid, so checking a position looks only at obstacles nearby.
+/// Every obstacle is put into all cells its circle extended by the margin overlaps.
+#[derive(Clone, Debug)]
+pub struct ObstacleIndex {
+    origin: Vec2,
+    cell_size: f64,
+    columns: usize,
+    rows: usize,
+    /// Radius added to every obstacle, usually the unit radius
+    margin: f64,
+    cells: Vec<Vec<Obstacle>>,
+}
+
+impl ObstacleIndex {
+    pub fn new(obstacles: &[Obstacle], margin: f64, cell_size: f64) -> Self {
+        if obstacles.is_empty() {
+            return Self {
+                origin: Vec2::zero(),
+                cell_size,
+                columns: 0,
+                rows: 0,
+                margin,
+                cells: Vec::new(),
+            };
+        }
+        let reach = |o: &Obstacle| o.radius + margin;
+        let min_x = obstacles
+            .iter()
+            .map(|o| o.position.x - reach(o))
+            .fold(f64::INFINITY, f64::min);
+        let min_y = obstacles
+            .iter()
+            .map(|o| o.position.y - reach(o))
+            .fold(f64::INFINITY, f64::min);
+        let max_x = obstacles
+            .iter()
+            .map(|o| o.position.x + reach(o))
+            .fold(f64::NEG_INFINITY, f64::max);
+        let max_y = obstacles
+            .iter()
+            .map(|o| o.position.y + reach(o))
+            .fold(f64::NEG_INFINITY, f64::max);
+
+        let origin = Vec2::new(min_x, min_y);
+        let columns = ((max_x - min_x) / cell_size).floor() as usize + 1;
+        let rows = ((max_y - min_y) / cell_size).floor() as usize + 1;
+        let mut cells = vec![Vec::new(); columns * rows];
+        for obstacle in obstacles {
+            let extent = Vec2::new(reach(obstacle), reach(obstacle));
+            let from = (obstacle.position - origin - extent) / cell_size;
+            let to = (obstacle.position - origin + extent) / cell_size;
+            for column in
+                from.x.floor().max(0.0) as usize..=(to.x.floor() as usize).min(columns - 1)
+            {
+                for row in from.y.floor().max(0.0) as usize..=(to.y.floor() as usize).min(rows - 1)
+                {
+                    cells[row * columns + column].push(obstacle.clone());
+                }
+            }
+        }
+
+        Self {
+            origin,
+            cell_size,
+            columns,
+            rows,
+            margin,
+            cells,
+        }
+    }
+
+    /// Obstacles whose extended circle may contain the position
+    pub fn near(&self, position: Vec2) -> &[Obstacle] {
+        let cell = (position - self.origin) / self.cell_size;
+        if cell.x < 0.0 || cell.y < 0.0 {
+            return &[];
+        }
+        let (column, row) = (cell.x as usize, cell.y as usize);
+        if column >= self.columns || row >= self.rows {
+            return &[];
+        }
+        &self.cells[row * self.columns + column]
+    }
+
+    /// Whether the position is inside of an obstacle extended by the margin
+    pub fn is_blocked(&self, position: Vec2) -> bool {
+        self.near(position)
+            .iter()
+            .any(|o| o.as_circle(self.margin).contains(&position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn obstacle(id: i32, x: f64, y: f64, radius: f64) -> Obstacle {
+        Obstacle {
+            id,
+            position: Vec2::new(x, y),
+            radius,
+            can_see_through: false,
+            can_shoot_through: false,
+        }
+    }
+
+    #[test]
+    fn matches_full_scan() {
+        let obstacles = vec![
+            obstacle(0, 0.0, 0.0, 2.0),
+            obstacle(1, 10.0, 3.0, 1.0),
+            obstacle(2, -7.0, 12.0, 4.0),
+        ];
+        let index = ObstacleIndex::new(&obstacles, 1.0, 2.5);
+        for i in -40..40 {
+            for j in -40..40 {
+                let position = Vec2::new(i as f64 * 0.5, j as f64 * 0.5);
+                let expected = obstacles
+                    .iter()
+                    .any(|o| o.as_circle(1.0).contains(&position));
+                assert_eq!(index.is_blocked(position), expected, "{:?}", position);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_index_blocks_nothing() {
+        let index = ObstacleIndex::new(&[], 1.0, 2.5);
+        assert!(!index.is_blocked(Vec2::zero()));
+    }
+}
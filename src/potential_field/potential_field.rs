@@ -1,9 +1,13 @@
+use crate::looting::LootUtility;
 use crate::model::*;
-use crate::potential_field::FightMode;
-use std::collections::HashSet;
+use crate::potential_field::*;
+use std::collections::{HashMap, HashSet};
 use std::f64::consts::PI;
 use std::ops::Not;
 
+/// Size of the obstacle index cells, in unit radii
+const OBSTACLE_CELL_SIZE: f64 = 4.0;
+
 pub fn normalize_angle(angle: f64) -> f64 {
     let mut angle = angle;
     while angle < -PI {
@@ -17,8 +21,10 @@ pub fn normalize_angle(angle: f64) -> f64 {
 
 pub struct PotentialField {
     constants: Constants,
+    obstacles: ObstacleIndex,
     seeing_units: Vec<Unit>,
-    pub old_enemies: Vec<Unit>,
+    team: Vec<Unit>,
+    pub enemies: EnemyTracker,
     seeing_projectiles: Vec<Projectile>,
     pub dangerous_projectiles: Vec<Projectile>,
     pub old_projectiles: Vec<Projectile>,
@@ -26,7 +32,15 @@ pub struct PotentialField {
     pub shooting_sounds: Vec<(Sound, Vec2, i32)>,
     pub hit_sounds: Vec<(Sound, i32)>,
     pub steps_sounds: Vec<(Sound, i32)>,
-    pub loot: Vec<Loot>,
+    pub loot: LootMemory,
+    /// How much every item is worth to our units
+    pub loot_utility: LootUtility,
+    /// Terms used when there are no dangerous projectiles around
+    pub terms: HashMap<Behaviour, FieldTerms>,
+    /// Terms used when a projectile is going to hit the unit
+    pub dodge_terms: HashMap<Behaviour, FieldTerms>,
+    /// Enemy assigned to each of our units by the team coordinator
+    pub targets: HashMap<i32, i32>,
     current_tick: i32,
     my_id: i32,
 }
@@ -35,8 +49,14 @@ impl PotentialField {
     pub fn new(constants: &Constants) -> Self {
         Self {
             constants: constants.clone(),
+            obstacles: ObstacleIndex::new(
+                &constants.obstacles,
+                constants.unit_radius,
+                constants.unit_radius * OBSTACLE_CELL_SIZE,
+            ),
             seeing_units: Vec::new(),
-            old_enemies: Vec::new(),
+            team: Vec::new(),
+            enemies: EnemyTracker::new(constants),
             seeing_projectiles: Vec::new(),
             old_projectiles: Vec::new(),
             dangerous_projectiles: Vec::new(),
@@ -44,69 +64,112 @@ impl PotentialField {
             shooting_sounds: Vec::new(),
             hit_sounds: Vec::new(),
             steps_sounds: Vec::new(),
-            loot: Vec::new(),
+            loot: LootMemory::new(constants),
+            loot_utility: LootUtility::new(constants),
+            terms: Behaviour::ALL
+                .iter()
+                .map(|&mode| (mode, Self::default_terms(mode)))
+                .collect(),
+            dodge_terms: Behaviour::ALL
+                .iter()
+                .map(|&mode| (mode, Self::default_dodge_terms(mode)))
+                .collect(),
+            targets: HashMap::new(),
             current_tick: 0,
             my_id: 0,
         }
     }
 
+    pub fn default_terms(behaviour: Behaviour) -> FieldTerms {
+        let weights = match behaviour {
+            Behaviour::Spawn => return Self::default_spawn_terms(),
+            Behaviour::Reposition => StateWeights::new(3.0, 1.0, 0.5, 0.0),
+            Behaviour::Retreat => StateWeights::new(1.0, 2.0, 0.5, 0.0),
+            Behaviour::Heal => StateWeights::new(1.0, 2.0, 0.5, 0.0),
+            Behaviour::Engage => StateWeights::new(1.0, 1.0, 0.5, 1.0),
+            Behaviour::Kite => StateWeights::new(1.0, 1.0, 0.5, 1.0),
+            Behaviour::Loot => StateWeights::new(1.0, 1.0, 2.0, 0.0),
+        };
+        let terms = FieldTerms::new()
+            .with(ZONE.weighted(weights.zone))
+            .with(OUTSIDE)
+            .with(HIT_SOUNDS)
+            .with(STEPS_SOUNDS)
+            .with(ENEMIES)
+            .with(DANGER.weighted(weights.danger))
+            .with(COVER.weighted(Self::cover_weight(behaviour)))
+            .with(ALLIES)
+            .with(LOOT.weighted(weights.loot));
+        if weights.target > 0.0 {
+            terms.with(TARGET.weighted(weights.target))
+        } else {
+            terms
+        }
+    }
+
+    pub fn default_dodge_terms(behaviour: Behaviour) -> FieldTerms {
+        FieldTerms::new()
+            .with(PROJECTILES)
+            .with(OUTSIDE)
+            .with(SHOOTING_SOUNDS)
+            .with(ENEMIES)
+            .with(DANGER)
+            .with(COVER.weighted(Self::cover_weight(behaviour)))
+    }
+
+    /// Out-gunned units should hide instead of running in the open
+    fn cover_weight(behaviour: Behaviour) -> f64 {
+        match behaviour {
+            Behaviour::Engage => 0.5,
+            Behaviour::Loot | Behaviour::Reposition => 1.0,
+            Behaviour::Kite => 2.0,
+            Behaviour::Spawn | Behaviour::Retreat | Behaviour::Heal => 3.0,
+        }
+    }
+
+    /// Spawning units can't be hurt, they look for a good place to land
+    pub fn default_spawn_terms() -> FieldTerms {
+        FieldTerms::new()
+            .with(ZONE.weighted(5.0))
+            .with(OUTSIDE)
+            .with(SHOOTING_SOUNDS)
+            .with(HIT_SOUNDS)
+            .with(STEPS_SOUNDS)
+            .with(ENEMIES)
+            .with(ALLIES)
+            .with(LOOT)
+    }
+
     pub fn update(&mut self, game: &Game) {
         self.current_tick = game.current_tick;
         self.my_id = game.my_id;
-        self.old_enemies.extend(self.seeing_units.iter().cloned());
         self.seeing_units = game.units.clone();
-        let seeing_units_ids = self
-            .seeing_units
+        self.team = game
+            .units
             .iter()
-            .map(|u| u.id)
-            .collect::<HashSet<_>>();
-        self.old_enemies.retain(|e| {
-            !seeing_units_ids.contains(&e.id)
+            .filter(|u| u.player_id == game.my_id)
+            .cloned()
+            .collect();
+        self.enemies.update(game);
+        self.seeing_projectiles = game.projectiles.clone();
+        self.zone = game.zone.clone();
+        let dt = 1.0 / self.constants.ticks_per_second;
+        let obstacles = &self.constants.obstacles;
+        let unit_radius = self.constants.unit_radius;
+        self.old_projectiles.retain(|p| {
+            !obstacles
+                .iter()
+                .filter(|o| !o.can_shoot_through)
+                .any(|o| p.hits_circle_within(&o.as_circle(0.0), dt))
                 && !game
                     .units
                     .iter()
-                    .filter(|u| u.player_id == game.my_id)
-                    .any(|u| {
-                        u.is_in_fov(e.position, &self.constants)
-                            && !game
-                                .units
-                                .iter()
-                                .filter(|u| u.player_id == game.my_id)
-                                .all(|u| {
-                                    u.position.distance_to(&e.position)
-                                        > self.constants.view_distance * 1.1
-                                })
-                    })
-        });
-        self.old_enemies.iter_mut().for_each(|enemy| {
-            let closest_my_unit = game
-                .units
-                .iter()
-                .filter(|u| u.player_id == game.my_id)
-                .min_by(|a, b| {
-                    a.position
-                        .square_distance_to(&enemy.position)
-                        .partial_cmp(&b.position.square_distance_to(&enemy.position))
-                        .unwrap()
-                });
-            if let Some(closest_my_unit) = closest_my_unit {
-                enemy.direction = (closest_my_unit.position - enemy.position).normalize();
-            }
-            enemy.weapon = Some(BOW);
-            enemy.ammo[BOW as usize] = 25;
-            if let Some(remaining_spawn_time) = enemy.remaining_spawn_time {
-                enemy.remaining_spawn_time = if remaining_spawn_time >= 0.0 {
-                    Some(remaining_spawn_time - 1.0 / self.constants.ticks_per_second)
-                } else {
-                    None
-                };
-            }
+                    .filter(|u| u.id != p.shooter_id)
+                    .any(|u| p.hits_circle_within(&u.as_circle(unit_radius), dt))
         });
-        self.seeing_projectiles = game.projectiles.clone();
-        self.zone = game.zone.clone();
         self.old_projectiles
             .iter_mut()
-            .for_each(|p| p.life_time -= 1.0 / self.constants.ticks_per_second);
+            .for_each(|p| *p = p.advanced(dt));
         let seeing_projectiles_ids = self
             .seeing_projectiles
             .iter()
@@ -146,10 +209,7 @@ impl PotentialField {
                 .iter()
                 .filter(|sound| {
                     let name = &self.constants.sounds[sound.type_index as usize].name;
-                    match name.as_str() {
-                        "Wand" | "Staff" | "Bow" => true,
-                        _ => false,
-                    }
+                    matches!(name.as_str(), "Wand" | "Staff" | "Bow")
                 })
                 .filter(|sound| {
                     !game
@@ -178,10 +238,7 @@ impl PotentialField {
                 .iter()
                 .filter(|sound| {
                     let name = &self.constants.sounds[sound.type_index as usize].name;
-                    match name.as_str() {
-                        "WandHit" | "StaffHit" | "BowHit" => true,
-                        _ => false,
-                    }
+                    matches!(name.as_str(), "WandHit" | "StaffHit" | "BowHit")
                 })
                 .filter(|sound| {
                     !game
@@ -203,10 +260,7 @@ impl PotentialField {
                 .iter()
                 .filter(|sound| {
                     let name = &self.constants.sounds[sound.type_index as usize].name;
-                    match name.as_str() {
-                        "Steps" => true,
-                        _ => false,
-                    }
+                    matches!(name.as_str(), "Steps")
                 })
                 .filter(|sound| {
                     !game
@@ -224,14 +278,7 @@ impl PotentialField {
         self.steps_sounds
             .retain(|&(.., tick)| game.current_tick - tick < 50);
 
-        self.loot.retain(|loot| {
-            !game
-                .units
-                .iter()
-                .filter(|p| p.player_id == game.my_id)
-                .any(|p| p.is_in_fov(loot.position, &self.constants))
-        });
-        self.loot.extend(game.loot.iter().cloned());
+        self.loot.update(game, &self.enemies);
     }
 
     pub fn points_around(&self, unit_id: i32) -> Vec<Vec2> {
@@ -249,25 +296,49 @@ impl PotentialField {
                     position.y + angle.sin() * self.constants.unit_radius,
                 )
             })
-            .filter(|p| {
-                self.constants
-                    .obstacles
-                    .iter()
-                    .any(|o| o.as_circle(self.constants.unit_radius).contains(p))
-                    .not()
-                    && self
-                        .seeing_units
-                        .iter()
-                        .filter(|u| u.id != unit_id)
-                        .any(|u| u.position.distance_to(p) <= self.constants.unit_radius * 2.0)
-                        .not()
-            })
+            .filter(|p| self.is_blocked(*p, unit_id).not())
             .collect()
     }
 
-    fn value_projectiles(&self, position: Vec2) -> f64 {
+    /// Whether the unit can't stand at the position because of obstacles or other units
+    pub fn is_blocked(&self, position: Vec2, unit_id: i32) -> bool {
+        self.obstacles.is_blocked(position)
+            || self
+                .seeing_units
+                .iter()
+                .filter(|u| u.id != unit_id)
+                .any(|u| u.position.distance_to(&position) <= self.constants.unit_radius * 2.0)
+    }
+
+    pub fn constants(&self) -> &Constants {
+        &self.constants
+    }
+
+    /// Obstacles extended by the unit radius
+    pub fn obstacles(&self) -> &ObstacleIndex {
+        &self.obstacles
+    }
+
+    pub fn zone(&self) -> &Zone {
+        &self.zone
+    }
+
+    pub fn current_tick(&self) -> i32 {
+        self.current_tick
+    }
+
+    pub fn value_projectiles(&self, position: Vec2) -> f64 {
+        self.value_projectiles_at(position, 0.0)
+    }
+
+    /// Projectiles are moved along their velocity for given seconds
+    pub fn value_projectiles_at(&self, position: Vec2, seconds: f64) -> f64 {
         let mut value = 0.0;
         for projectile in self.dangerous_projectiles.iter() {
+            let projectile = projectile.advanced(seconds);
+            if projectile.life_time <= 0.0 {
+                continue;
+            }
             let line = projectile.as_line();
 
             let distance = line.distance_to_point(&position);
@@ -286,7 +357,7 @@ impl PotentialField {
         value
     }
 
-    fn value_zone(&self, position: Vec2) -> f64 {
+    pub fn value_zone(&self, position: Vec2) -> f64 {
         let distance = self.zone.next_center.distance_to(&position);
         let wanna_radius = (self.zone.next_radius - self.constants.unit_radius * 4.0)
             .max(self.constants.unit_radius * 2.0);
@@ -297,9 +368,18 @@ impl PotentialField {
         1.0 - distance / wanna_radius
     }
 
-    fn value_outside(&self, position: Vec2) -> f64 {
-        let distance = self.zone.current_center.distance_to(&position);
-        let max_distance = self.zone.current_radius - self.constants.unit_radius * 4.0;
+    pub fn value_outside(&self, position: Vec2) -> f64 {
+        self.value_outside_at(position, 0.0)
+    }
+
+    /// Zone is shrunk and moved for given seconds
+    pub fn value_outside_at(&self, position: Vec2, seconds: f64) -> f64 {
+        let distance = self
+            .zone
+            .center_after(seconds, &self.constants)
+            .distance_to(&position);
+        let max_distance =
+            self.zone.radius_after(seconds, &self.constants) - self.constants.unit_radius * 4.0;
         if distance > max_distance {
             return -(distance - max_distance);
         }
@@ -307,7 +387,7 @@ impl PotentialField {
         0.0
     }
 
-    fn value_shooting_sounds(&self, position: Vec2) -> f64 {
+    pub fn value_shooting_sounds(&self, position: Vec2) -> f64 {
         let mut value = 0.0;
         for (sound, my_pos, tick) in self.shooting_sounds.iter() {
             let tick_k = 1.0 - (self.current_tick - tick) as f64 / 50.0;
@@ -324,7 +404,7 @@ impl PotentialField {
         value
     }
 
-    fn value_hit_sounds(&self, position: Vec2) -> f64 {
+    pub fn value_hit_sounds(&self, position: Vec2) -> f64 {
         let mut value = 0.0;
         for (sound, tick) in self.hit_sounds.iter() {
             let tick_k = 1.0 - (self.current_tick - tick) as f64 / 50.0;
@@ -339,7 +419,7 @@ impl PotentialField {
         value
     }
 
-    fn value_steps_sounds(&self, position: Vec2) -> f64 {
+    pub fn value_steps_sounds(&self, position: Vec2) -> f64 {
         let mut value = 0.0;
         for (sound, tick) in self.steps_sounds.iter() {
             let tick_k = 1.0 - (self.current_tick - tick) as f64 / 50.0;
@@ -354,19 +434,15 @@ impl PotentialField {
         value
     }
 
-    fn enemy_val(&self, position: Vec2, me: &Unit, fight_mode: FightMode, enemy: &Unit) -> f64 {
+    fn enemy_val(&self, position: Vec2, me: &Unit, behaviour: Behaviour, enemy: &Unit) -> f64 {
         let mut value = 0.0;
         let my_range = me.range(&self.constants);
         let distance_to_enemy = enemy.position.distance_to(&position);
         let enemy_range = enemy.range(&self.constants);
-        match fight_mode {
-            FightMode::Attack => {
+        match (behaviour, my_range) {
+            (Behaviour::Engage | Behaviour::Loot, Some(my_range)) => {
                 // отходим на границу моего ренджа, подходим ближе если не можем стрелять, не ближе 20.0 примерно (уверенного попадания из лука и огнемета), нападаем если у него нет оружия
-                let target_distance = if let Some(my_range) = my_range {
-                    my_range * 0.75
-                } else {
-                    40.0
-                };
+                let target_distance = my_range * 0.75;
 
                 if distance_to_enemy < target_distance {
                     value -= 1.0 - distance_to_enemy / target_distance;
@@ -374,12 +450,35 @@ impl PotentialField {
                     value += (distance_to_enemy / target_distance - 1.0) * 0.5;
                 }
             }
-            FightMode::Defend => {
+            (Behaviour::Kite, Some(my_range)) => {
+                // staying just out of their range, and inside of ours if it is longer
+                let outside = enemy_range.map_or(my_range * 0.75, |enemy_range| {
+                    enemy_range + self.constants.unit_radius * 2.0
+                });
+                let preferred = if outside < my_range {
+                    (outside + my_range) / 2.0
+                } else {
+                    outside
+                };
+                value -= ((distance_to_enemy - preferred).abs() / preferred).min(1.0);
+            }
+            (Behaviour::Retreat, Some(_)) => {
+                // backing off behind obstacles, exposed positions in their range are penalized
+                if let Some(enemy_range) = enemy_range {
+                    let exposure = 1.0 - distance_to_enemy / enemy_range;
+                    if !is_line_of_fire_blocked(enemy.position, position, &self.constants) {
+                        value -= exposure;
+                    } else if exposure > 0.0 {
+                        value += 0.5 * exposure;
+                    }
+                }
+            }
+            (Behaviour::Heal | Behaviour::Reposition, Some(my_range)) => {
                 // отходим за границу его ренджа, на наш не особо обращаем внимание, но нападаем если у него нет оружия
                 if let Some(enemy_range) = enemy_range {
                     value -= 1.0 - distance_to_enemy / enemy_range;
                 } else {
-                    let target_distance = my_range.unwrap_or(40.0) * 0.75;
+                    let target_distance = my_range * 0.75;
                     if distance_to_enemy < target_distance {
                         value -= 1.0 - distance_to_enemy / target_distance;
                     } else if distance_to_enemy < target_distance * 2.0 {
@@ -387,7 +486,7 @@ impl PotentialField {
                     }
                 }
             }
-            FightMode::RunWithNoWeapons => {
+            _ => {
                 // убегаем за границу видимости
                 let view_distance = self.constants.view_distance + self.constants.unit_radius * 2.0;
                 if distance_to_enemy < view_distance {
@@ -414,24 +513,79 @@ impl PotentialField {
         value
     }
 
-    fn value_enemies(&self, position: Vec2, me: &Unit, fight_mode: FightMode) -> f64 {
+    pub fn value_enemies(&self, position: Vec2, me: &Unit, behaviour: Behaviour) -> f64 {
         let mut value = 0.0;
 
-        for enemy in self
-            .seeing_units
+        for enemy in self.enemies.iter() {
+            value += self.enemy_val(position, me, behaviour, &enemy.unit) * enemy.confidence;
+        }
+
+        value
+    }
+
+    /// Negative sum of line of fire dangers of all tracked enemies
+    pub fn value_danger(&self, position: Vec2) -> f64 {
+        -self
+            .enemies
             .iter()
-            .filter(|u| u.player_id != self.my_id)
-        {
-            value += self.enemy_val(position, me, fight_mode, enemy);
+            .map(|enemy| line_of_fire_danger(enemy, position, &self.constants))
+            .sum::<f64>()
+    }
+
+    /// Sum of strengths of threats whose line of fire to the position is blocked
+    pub fn value_cover(&self, position: Vec2) -> f64 {
+        let mut value = 0.0;
+        for enemy in self.enemies.iter() {
+            let strength = threat_strength(enemy, &self.constants);
+            if strength == 0.0 {
+                continue;
+            }
+            let range = enemy.unit.range(&self.constants).unwrap_or(0.0);
+            if enemy.unit.position.distance_to(&position) > range + enemy.uncertainty {
+                continue;
+            }
+            if is_line_of_fire_blocked(enemy.unit.position, position, &self.constants) {
+                value += strength;
+            }
         }
 
-        for enemy in self.old_enemies.iter() {
-            value += self.enemy_val(position, me, fight_mode, enemy);
+        for projectile in self.dangerous_projectiles.iter() {
+            if is_line_of_fire_blocked(projectile.position, position, &self.constants) {
+                let weapon = &self.constants.weapons[projectile.weapon_type_index as usize];
+                value += weapon.projectile_damage / self.constants.unit_health;
+            }
         }
 
         value
     }
 
+    /// Keeping the assigned target at 3/4 of our range with a clear line of fire
+    pub fn value_target(&self, position: Vec2, me: &Unit) -> f64 {
+        let target = match self
+            .targets
+            .get(&me.id)
+            .and_then(|id| self.enemies.get(*id))
+        {
+            Some(target) => target,
+            None => return 0.0,
+        };
+        let range = match me.range(&self.constants) {
+            Some(range) => range,
+            None => return 0.0,
+        };
+        let preferred = range * 0.75;
+        let distance = target.unit.position.distance_to(&position);
+        let distance_value = 1.0 - ((distance - preferred).abs() / preferred).min(2.0);
+        let fire_value = if is_line_of_fire_blocked(target.unit.position, position, &self.constants)
+        {
+            0.0
+        } else {
+            1.0
+        };
+
+        (distance_value + fire_value) / 2.0 * target.confidence
+    }
+
     pub fn value_allies(&self, position: Vec2, me: &Unit) -> f64 {
         let mut value = 0.0;
 
@@ -456,26 +610,33 @@ impl PotentialField {
     pub fn value_loot(&self, position: Vec2, me: &Unit) -> f64 {
         let mut value = 0.0;
 
-        for loot in self
-            .loot
-            .iter()
-            .filter(|l| l.is_useful_to_me(me, &self.constants))
-        {
-            let distance_to_loot = loot.position.distance_to(&position);
+        for item in self.loot.iter().filter(|i| i.is_available()) {
+            let distance_to_loot = item.loot.position.distance_to(&position);
             let max_distance = self.constants.unit_radius * 10.0;
             if distance_to_loot < max_distance {
-                value += 1.0 - distance_to_loot / max_distance;
+                let utility = self.loot_utility.value(
+                    &item.loot,
+                    me,
+                    &self.team,
+                    &self.zone,
+                    &self.constants,
+                );
+                value += utility
+                    * (1.0 - distance_to_loot / max_distance)
+                    * (1.0 - item.taken_probability);
             }
         }
 
         value
     }
 
+    /// Our units, including the unit itself
+    pub fn team(&self) -> &[Unit] {
+        &self.team
+    }
+
     pub fn im_inside_obstacle(&self, me: &Unit) -> bool {
-        self.constants.obstacles.iter().any(|o| {
-            o.as_circle(self.constants.unit_radius)
-                .contains(&me.position)
-        })
+        self.obstacles.is_blocked(me.position)
     }
 
     pub fn im_outside(&self, me: &Unit) -> bool {
@@ -503,48 +664,77 @@ impl PotentialField {
                 >= (self.zone.current_radius - self.constants.unit_radius * 4.0)
     }
 
-    pub fn sounds(&self) -> Vec<Sound> {
-        let mut sounds = self
-            .shooting_sounds
-            .iter()
-            .map(|(sound, ..)| sound.clone())
-            .collect::<Vec<Sound>>();
-
-        sounds.extend(self.hit_sounds.iter().map(|(sound, _)| sound.clone()));
-        sounds.extend(self.steps_sounds.iter().map(|(sound, _)| sound.clone()));
-
-        sounds
-    }
-
-    pub fn value(&self, position: Vec2, me: &Unit, fight_mode: FightMode) -> f64 {
+    /// Terms which are used for the unit right now
+    pub fn active_terms(&self, me: &Unit, behaviour: Behaviour) -> &FieldTerms {
         if self
             .dangerous_projectiles
             .iter()
             .any(|p| p.is_dangerous(me, &self.constants))
         {
-            return self.value_projectiles(position) * 3.0
-                + self.value_outside(position) * 5.0
-                + self.value_shooting_sounds(position)
-                + self.value_enemies(position, me, fight_mode);
+            &self.dodge_terms[&behaviour]
+        } else {
+            &self.terms[&behaviour]
         }
+    }
+
+    pub fn value(&self, position: Vec2, me: &Unit, behaviour: Behaviour) -> f64 {
+        self.active_terms(me, behaviour)
+            .value(self, position, me, behaviour)
+    }
 
-        self.value_zone(position)
-            + self.value_outside(position) * 5.0
-            + self.value_hit_sounds(position)
-            + self.value_steps_sounds(position)
-            + self.value_enemies(position, me, fight_mode)
-            + self.value_allies(position, me)
-            + self.value_loot(position, me)
-    }
-
-    pub fn value_unspawned(&self, position: Vec2, me: &Unit) -> f64 {
-        self.value_zone(position) * 5.0
-            + self.value_outside(position) * 5.0
-            + self.value_shooting_sounds(position)
-            + self.value_hit_sounds(position)
-            + self.value_steps_sounds(position)
-            + self.value_enemies(position, me, FightMode::RunWithNoWeapons)
-            + self.value_allies(position, me)
-            + self.value_loot(position, me)
+    /// Value of the field after given seconds from the current tick
+    pub fn value_at(&self, position: Vec2, me: &Unit, behaviour: Behaviour, seconds: f64) -> f64 {
+        self.active_terms(me, behaviour)
+            .value_at(self, position, me, behaviour, seconds)
+    }
+
+    pub fn value_breakdown(
+        &self,
+        position: Vec2,
+        me: &Unit,
+        behaviour: Behaviour,
+    ) -> ValueBreakdown {
+        self.active_terms(me, behaviour)
+            .breakdown(self, position, me, behaviour)
+    }
+
+    pub fn rasterize(
+        &self,
+        me: &Unit,
+        behaviour: Behaviour,
+        area: GridArea,
+        step: f64,
+    ) -> FieldGrid {
+        self.rasterize_with(area, step, |position| self.value(position, me, behaviour))
+    }
+
+    pub fn rasterize_with<F>(&self, area: GridArea, step: f64, f: F) -> FieldGrid
+    where
+        F: Fn(Vec2) -> f64,
+    {
+        let (center, half_size) = match area {
+            GridArea::Window { center, half_size } => (center, half_size),
+            GridArea::Zone => (self.zone.current_center, self.zone.current_radius),
+        };
+        FieldGrid::from_fn(center, half_size, step, f)
+    }
+}
+
+/// Weights of the terms which differ between behaviours
+struct StateWeights {
+    zone: f64,
+    danger: f64,
+    loot: f64,
+    target: f64,
+}
+
+impl StateWeights {
+    fn new(zone: f64, danger: f64, loot: f64, target: f64) -> Self {
+        Self {
+            zone,
+            danger,
+            loot,
+            target,
+        }
     }
 }
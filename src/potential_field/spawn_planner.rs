@@ -0,0 +1,148 @@
+use crate::model::*;
+use crate::potential_field::{threat_strength, PotentialField};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+/// Chooses where to land among the points reachable before the spawn ends
+#[derive(Clone, Debug)]
+pub struct SpawnPlanner {
+    /// Number of circles of candidate points around the unit
+    pub rings: usize,
+    /// Number of candidate points on every circle
+    pub points_per_ring: usize,
+    /// Loot further than that is not considered, in unit radii
+    pub loot_radius: f64,
+    /// Enemies further than that are considered harmless, in unit radii
+    pub safe_enemy_distance: f64,
+    /// Seconds after landing the spot should stay inside the zone
+    pub zone_horizon: f64,
+    pub loot_weight: f64,
+    pub enemy_weight: f64,
+    pub zone_weight: f64,
+    pub collision_weight: f64,
+    /// Bonus of the previously chosen spot, to not jump between similar ones
+    pub stickiness: f64,
+    landings: HashMap<i32, Vec2>,
+}
+
+impl Default for SpawnPlanner {
+    fn default() -> Self {
+        Self {
+            rings: 4,
+            points_per_ring: 16,
+            loot_radius: 15.0,
+            safe_enemy_distance: 30.0,
+            zone_horizon: 10.0,
+            loot_weight: 1.0,
+            enemy_weight: 1.0,
+            zone_weight: 5.0,
+            collision_weight: 1.0,
+            stickiness: 0.1,
+            landings: HashMap::new(),
+        }
+    }
+}
+
+impl SpawnPlanner {
+    fn candidates(&self, me: &Unit, reach: f64) -> Vec<Vec2> {
+        let mut candidates = vec![me.position];
+        if let Some(&landing) = self.landings.get(&me.id) {
+            candidates.push(landing);
+        }
+        for ring in 1..=self.rings {
+            let radius = reach * ring as f64 / self.rings as f64;
+            candidates.extend((0..self.points_per_ring).map(|i| {
+                let angle = i as f64 * 2.0 * PI / self.points_per_ring as f64;
+                me.position + Vec2::new(angle.cos(), angle.sin()) * radius
+            }));
+        }
+        candidates
+    }
+
+    pub fn score(&self, pp: &PotentialField, me: &Unit, landing: Vec2, seconds: f64) -> f64 {
+        let constants = pp.constants();
+        let zone = pp.zone();
+
+        let loot_radius = self.loot_radius * constants.unit_radius;
+        let loot = pp
+            .loot
+            .iter()
+            .filter(|i| i.is_available())
+            .map(|i| {
+                let distance = i.loot.position.distance_to(&landing);
+                let utility = pp
+                    .loot_utility
+                    .value(&i.loot, me, pp.team(), zone, constants);
+                utility * (1.0 - distance / loot_radius).max(0.0) * (1.0 - i.taken_probability)
+            })
+            .sum::<f64>();
+
+        let safe_distance = self.safe_enemy_distance * constants.unit_radius;
+        let enemies = pp
+            .enemies
+            .iter()
+            .map(|e| {
+                let distance = (e.unit.position.distance_to(&landing) - e.uncertainty).max(0.0);
+                e.confidence
+                    * (1.0 + threat_strength(e, constants))
+                    * (1.0 - distance / safe_distance).max(0.0)
+            })
+            .sum::<f64>();
+
+        let zone_seconds = seconds + self.zone_horizon;
+        let safe_radius = zone.radius_after(zone_seconds, constants) - constants.unit_radius * 2.0;
+        let safe_center = zone.center_after(zone_seconds, constants);
+        let outside =
+            (landing.distance_to(&safe_center) - safe_radius).max(0.0) / constants.unit_radius;
+
+        // landing on an obstacle or a unit hurts until we move off it
+        let collision = if pp.is_blocked(landing, me.id) {
+            constants.spawn_collision_damage_per_second / constants.unit_health
+        } else {
+            0.0
+        };
+
+        let previous = match self.landings.get(&me.id) {
+            Some(previous) if previous.distance_to(&landing) < constants.unit_radius => {
+                self.stickiness
+            }
+            _ => 0.0,
+        };
+
+        self.loot_weight * loot
+            - self.enemy_weight * enemies
+            - self.zone_weight * outside
+            - self.collision_weight * collision
+            + previous
+    }
+
+    /// Best landing spot for the spawning unit, remembered until the unit lands
+    pub fn plan(&mut self, pp: &PotentialField, me: &Unit) -> Option<Vec2> {
+        let seconds = match me.remaining_spawn_time {
+            Some(seconds) => seconds,
+            None => {
+                self.landings.remove(&me.id);
+                return None;
+            }
+        };
+        let reach = seconds * pp.constants().spawn_movement_speed;
+
+        let landing = self
+            .candidates(me, reach)
+            .into_iter()
+            .filter(|p| p.distance_to(&me.position) <= reach + f64::EPSILON)
+            .map(|p| (p, self.score(pp, me, p, seconds)))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(p, _)| p)?;
+        self.landings.insert(me.id, landing);
+        Some(landing)
+    }
+
+    /// Target velocity which brings the unit to the landing spot and keeps it there
+    pub fn velocity_to(&self, me: &Unit, landing: Vec2, constants: &Constants) -> Vec2 {
+        let offset = landing - me.position;
+        let speed =
+            (offset.length() * constants.ticks_per_second).min(constants.spawn_movement_speed);
+        offset.normalize() * speed
+    }
+}
@@ -0,0 +1,147 @@
+use crate::model::*;
+use crate::potential_field::{Behaviour, PotentialField};
+use std::f64::consts::PI;
+
+/// Sequence of target velocities and positions they lead to
+#[derive(Clone, Debug)]
+pub struct Trajectory {
+    pub target_velocities: Vec<Vec2>,
+    pub positions: Vec<Vec2>,
+    pub score: f64,
+    velocity: Vec2,
+}
+
+impl Trajectory {
+    pub fn first_velocity(&self) -> Option<Vec2> {
+        self.target_velocities.first().cloned()
+    }
+
+    pub fn last_position(&self) -> Option<Vec2> {
+        self.positions.last().cloned()
+    }
+}
+
+/// Beam search over several steps of constant target velocity
+#[derive(Clone, Debug)]
+pub struct TrajectoryPlanner {
+    /// Number of steps in a trajectory
+    pub depth: usize,
+    /// Number of best trajectories kept after every step
+    pub beam_width: usize,
+    /// Number of moving directions tried on every step, standing still is always tried too
+    pub directions: usize,
+    /// Ticks of keeping the same target velocity
+    pub ticks_per_step: usize,
+    /// Multiplier of the value for every next step
+    pub discount: f64,
+    /// Weight of the progress towards the goal, 1 is moving straight to it at full speed
+    pub goal_weight: f64,
+}
+
+impl Default for TrajectoryPlanner {
+    fn default() -> Self {
+        Self {
+            depth: 4,
+            beam_width: 6,
+            directions: 12,
+            ticks_per_step: 5,
+            discount: 0.8,
+            goal_weight: 1.0,
+        }
+    }
+}
+
+impl TrajectoryPlanner {
+    fn candidate_velocities(&self, max_speed: f64) -> Vec<Vec2> {
+        let mut velocities = (0..self.directions)
+            .map(|i| {
+                let angle = i as f64 * 2.0 * PI / self.directions as f64;
+                Vec2::new(angle.cos(), angle.sin()) * max_speed
+            })
+            .collect::<Vec<Vec2>>();
+        velocities.push(Vec2::zero());
+        velocities
+    }
+
+    /// Simulates the step, None if the unit bumps into something
+    fn simulate(
+        &self,
+        pp: &PotentialField,
+        me: &Unit,
+        mut position: Vec2,
+        mut velocity: Vec2,
+        target_velocity: Vec2,
+    ) -> Option<(Vec2, Vec2)> {
+        let constants = pp.constants();
+        for _ in 0..self.ticks_per_step {
+            velocity = me.next_velocity(velocity, target_velocity, constants);
+            position += velocity / constants.ticks_per_second;
+            if pp.is_blocked(position, me.id) {
+                return None;
+            }
+        }
+        Some((position, velocity))
+    }
+
+    /// Best trajectory by the field value, and by the progress towards the goal if there is one
+    pub fn plan(
+        &self,
+        pp: &PotentialField,
+        me: &Unit,
+        behaviour: Behaviour,
+        max_speed: f64,
+        goal: Option<Vec2>,
+    ) -> Option<Trajectory> {
+        let constants = pp.constants();
+        let candidates = self.candidate_velocities(max_speed);
+        let step_seconds = self.ticks_per_step as f64 / constants.ticks_per_second;
+
+        let mut beam = vec![Trajectory {
+            target_velocities: Vec::new(),
+            positions: Vec::new(),
+            score: 0.0,
+            velocity: me.velocity,
+        }];
+
+        for step in 0..self.depth {
+            let seconds = step_seconds * (step + 1) as f64;
+            let k = self.discount.powi(step as i32);
+            let mut next = Vec::with_capacity(beam.len() * candidates.len());
+            for trajectory in beam.iter() {
+                let position = trajectory.last_position().unwrap_or(me.position);
+                for &target_velocity in candidates.iter() {
+                    if let Some((position, velocity)) =
+                        self.simulate(pp, me, position, trajectory.velocity, target_velocity)
+                    {
+                        let progress = goal.map_or(0.0, |goal| {
+                            (me.position.distance_to(&goal) - position.distance_to(&goal))
+                                / (max_speed * seconds).max(f64::EPSILON)
+                        });
+                        let value = pp.value_at(position, me, behaviour, seconds)
+                            + self.goal_weight * progress;
+                        let mut target_velocities = trajectory.target_velocities.clone();
+                        target_velocities.push(target_velocity);
+                        let mut positions = trajectory.positions.clone();
+                        positions.push(position);
+                        next.push(Trajectory {
+                            target_velocities,
+                            positions,
+                            score: trajectory.score + value * k,
+                            velocity,
+                        });
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            next.sort_by(|a, b| b.score.total_cmp(&a.score));
+            next.truncate(self.beam_width);
+            beam = next;
+        }
+
+        beam.into_iter()
+            .filter(|t| !t.target_velocities.is_empty())
+            .max_by(|a, b| a.score.total_cmp(&b.score))
+    }
+}